@@ -0,0 +1,34 @@
+extern crate actix_web;
+
+use actix_web::{App, HttpServer};
+use actix_web::middleware::from_fn;
+use weather_service::fake_upstream::{self, FakeUpstreamConfig};
+use weather_service::web::{logging, request_id};
+use std::env;
+use std::fs;
+
+/// Used when `RUST_LOG` is not set, request lines come from `weather_service`.
+const DEFAULT_FILTER: &str = "fake_upstream=info,weather_service=info";
+
+/// `fake_upstream [scenarios.json] [bind address]`, by default cfg/fake_upstream.json and 127.0.0.1:8089.
+///
+/// Logs the same JSON lines as the service, so the logs of an end to end run can be read together.
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    logging::init_with_default(DEFAULT_FILTER, None);
+
+    let path = env::args().nth(1).unwrap_or_else(|| String::from("cfg/fake_upstream.json"));
+    let bind = env::args().nth(2).unwrap_or_else(|| String::from("127.0.0.1:8089"));
+
+    let config: FakeUpstreamConfig = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    tracing::info!(bind = %bind, scenarios = %path, "fake upstream started");
+
+    HttpServer::new(move ||
+        App::new()
+            .wrap(from_fn(request_id::assign_request_id))
+            .configure(fake_upstream::configure(config.clone()))
+    )
+        .bind(bind)?
+        .run()
+        .await
+}
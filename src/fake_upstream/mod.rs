@@ -0,0 +1,226 @@
+//! Local stand-in for the Apixu and Yahoo APIs, lets the service run end to end without network.
+//!
+//! Point the providers at it with `apixu_base_uri` (`http://127.0.0.1:8089/v1`) and `yahoo_base_uri`
+//! (`http://127.0.0.1:8089/forecastrss`) in the service config. Every location gets a generated forecast
+//! starting today (UTC), unless a scenario is configured for it.
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{self, Data, Query, ServiceConfig};
+use chrono::{Duration as ChronoDuration, Utc};
+use weather_core::oauth::OAuthSigner;
+
+mod test;
+
+/// How the fake upstream answers for a location.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "scenario", rename_all = "snake_case")]
+pub enum Scenario {
+    Ok,
+    /// Answers normally after `delay_ms`, longer than the provider timeout (10s) means a timeout.
+    Slow { delay_ms: u64 },
+    ServerError,
+    InvalidJson,
+    UnknownLocation,
+    /// Forecast shorter than asked, `days` days starting today.
+    TruncatedHorizon { days: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YahooCredentials {
+    pub app_id: String,
+    pub user_key: String,
+    pub secret: String,
+}
+
+/// Scenarios by location as providers send it (`Moscow`, `55.75,37.61`), per upstream.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FakeUpstreamConfig {
+    #[serde(default)]
+    pub apixu: HashMap<String, Scenario>,
+    #[serde(default)]
+    pub yahoo: HashMap<String, Scenario>,
+    /// Key Apixu requests must carry, any key is accepted when not set.
+    #[serde(default)]
+    pub apixu_key: Option<String>,
+    /// Yahoo requests must be signed with these credentials, signatures are not checked when not set.
+    #[serde(default)]
+    pub yahoo_credentials: Option<YahooCredentials>,
+}
+
+impl FakeUpstreamConfig {
+    pub fn apixu(mut self, location: &str, scenario: Scenario) -> Self {
+        self.apixu.insert(location.to_owned(), scenario);
+        self
+    }
+
+    pub fn yahoo(mut self, location: &str, scenario: Scenario) -> Self {
+        self.yahoo.insert(location.to_owned(), scenario);
+        self
+    }
+
+    pub fn with_apixu_key(mut self, key: &str) -> Self {
+        self.apixu_key = Some(key.to_owned());
+        self
+    }
+
+    pub fn with_yahoo_credentials(mut self, credentials: YahooCredentials) -> Self {
+        self.yahoo_credentials = Some(credentials);
+        self
+    }
+}
+
+const APIXU_HORIZON_DAYS: u32 = 7;
+const YAHOO_HORIZON_DAYS: u32 = 10;
+
+/// Routes of both upstreams: `/v1/forecast.json`, `/v1/current.json` (Apixu) and `/forecastrss` (Yahoo).
+pub fn configure(config: FakeUpstreamConfig) -> impl FnOnce(&mut ServiceConfig) {
+    move |cfg| {
+        cfg.app_data(Data::new(config))
+            .route("/v1/forecast.json", web::get().to(apixu_forecast))
+            .route("/v1/current.json", web::get().to(apixu_current))
+            .route("/forecastrss", web::get().to(yahoo_forecast));
+    }
+}
+
+async fn apixu_forecast(config: Data<FakeUpstreamConfig>, query: Query<BTreeMap<String, String>>) -> HttpResponse {
+    let days = query.get("days").and_then(|days| days.parse().ok()).unwrap_or(APIXU_HORIZON_DAYS).min(APIXU_HORIZON_DAYS);
+
+    apixu_response(&config, &query, |days_available| {
+        let forecast_days: Vec<serde_json::Value> = (0..days.min(days_available))
+            .map(|offset| serde_json::json!({
+                "date": (Utc::now().date_naive() + ChronoDuration::days(offset.into())).format("%Y-%m-%d").to_string(),
+                "day": {"avgtemp_c": 10.0 + f64::from(offset), "avgtemp_f": 50.0 + 1.8 * f64::from(offset)}
+            }))
+            .collect();
+
+        serde_json::json!({"location": {"name": query.get("q"), "tz_id": "UTC"}, "forecast": {"forecastday": forecast_days}})
+    }).await
+}
+
+async fn apixu_current(config: Data<FakeUpstreamConfig>, query: Query<BTreeMap<String, String>>) -> HttpResponse {
+    apixu_response(&config, &query, |_| serde_json::json!({
+        "location": {"name": query.get("q")},
+        "current": {
+            "last_updated_epoch": Utc::now().timestamp(),
+            "temp_c": 10.0,
+            "wind_kph": 11.2,
+            "humidity": 70,
+            "condition": {"text": "Partly cloudy", "code": 1003}
+        }
+    })).await
+}
+
+async fn apixu_response<F>(config: &FakeUpstreamConfig, query: &BTreeMap<String, String>, body: F) -> HttpResponse
+    where F: FnOnce(u32) -> serde_json::Value
+{
+    let key_accepted = match (&config.apixu_key, query.get("key")) {
+        (Some(expected), Some(key)) => expected == key,
+        (Some(_), None) => false,
+        (None, key) => key.is_some(),
+    };
+    if !key_accepted {
+        return HttpResponse::Unauthorized().json(serde_json::json!({"error": {"code": 2006, "message": "API key provided is invalid"}}));
+    }
+
+    let location = query.get("q").cloned().unwrap_or_default();
+
+    match play(config.apixu.get(&location), APIXU_HORIZON_DAYS).await {
+        Ok(days) => HttpResponse::Ok().json(body(days)),
+        Err(Failure::UnknownLocation) =>
+            HttpResponse::BadRequest().json(serde_json::json!({"error": {"code": 1006, "message": "No matching location found."}})),
+        Err(failure) => failure.response(),
+    }
+}
+
+async fn yahoo_forecast(req: HttpRequest, config: Data<FakeUpstreamConfig>, query: Query<BTreeMap<String, String>>) -> HttpResponse {
+    if let Some(ref credentials) = config.yahoo_credentials {
+        if let Err(reason) = verify_yahoo_request(&req, credentials) {
+            return HttpResponse::Unauthorized().json(serde_json::json!({"error": reason}));
+        }
+    }
+
+    let location = match (query.get("location"), query.get("lat"), query.get("lon")) {
+        (Some(location), _, _) => location.clone(),
+        (None, Some(lat), Some(lon)) => format!("{},{}", lat, lon),
+        _ => String::new(),
+    };
+
+    match play(config.yahoo.get(&location), YAHOO_HORIZON_DAYS).await {
+        Ok(days) => {
+            let today = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+            let forecasts: Vec<serde_json::Value> = (0..days)
+                .map(|offset| serde_json::json!({
+                    "day": (today + ChronoDuration::days(offset.into())).format("%a").to_string(),
+                    "date": (today + ChronoDuration::days(offset.into())).timestamp(),
+                    "low": 8 + offset,
+                    "high": 14 + offset,
+                    "text": "Cloudy",
+                    "code": 26
+                }))
+                .collect();
+
+            HttpResponse::Ok().json(serde_json::json!({
+                "location": {"city": location, "timezone_id": "UTC"},
+                "current_observation": {
+                    "wind": {"speed": 11.27},
+                    "atmosphere": {"humidity": 72},
+                    "condition": {"text": "Cloudy", "code": 26, "temperature": 11},
+                    "pubDate": Utc::now().timestamp()
+                },
+                "forecasts": forecasts
+            }))
+        }
+        // Yahoo answers unknown locations with empty objects, not with an error status.
+        Err(Failure::UnknownLocation) =>
+            HttpResponse::Ok().json(serde_json::json!({"location": {}, "current_observation": {}, "forecasts": []})),
+        Err(failure) => failure.response(),
+    }
+}
+
+/// Checks the OAuth 1.0a header the way Yahoo does: consumer key and signature over method, url and parameters.
+fn verify_yahoo_request(req: &HttpRequest, credentials: &YahooCredentials) -> Result<(), String> {
+    let header = |name: &str| req.headers().get(name).and_then(|value| value.to_str().ok());
+
+    if header("X-Yahoo-App-Id") != Some(credentials.app_id.as_str()) {
+        return Err(String::from("Invalid X-Yahoo-App-Id"));
+    }
+
+    let connection = req.connection_info();
+    let url = format!("{}://{}{}", connection.scheme(), connection.host(), req.uri());
+
+    OAuthSigner::new(credentials.user_key.clone(), credentials.secret.clone())
+        .verify("GET", &url, &[], header("Authorization").unwrap_or(""))
+        .map_err(|e| e.to_string())
+}
+
+enum Failure {
+    ServerError,
+    InvalidJson,
+    UnknownLocation,
+}
+
+impl Failure {
+    fn response(&self) -> HttpResponse {
+        match self {
+            Failure::ServerError => HttpResponse::InternalServerError().body("Internal Server Error"),
+            Failure::InvalidJson => HttpResponse::Ok().content_type("application/json").body(r#"{"forecast": {"forecastday": [{"date": "#),
+            Failure::UnknownLocation => HttpResponse::BadRequest().finish(),
+        }
+    }
+}
+
+/// Plays the scenario: waits if it is slow, returns how many forecast days to serve or how to fail.
+async fn play(scenario: Option<&Scenario>, horizon_days: u32) -> Result<u32, Failure> {
+    match scenario.unwrap_or(&Scenario::Ok) {
+        Scenario::Ok => Ok(horizon_days),
+        Scenario::Slow { delay_ms } => {
+            actix_web::rt::time::sleep(Duration::from_millis(*delay_ms)).await;
+            Ok(horizon_days)
+        }
+        Scenario::ServerError => Err(Failure::ServerError),
+        Scenario::InvalidJson => Err(Failure::InvalidJson),
+        Scenario::UnknownLocation => Err(Failure::UnknownLocation),
+        Scenario::TruncatedHorizon { days } => Ok((*days).min(horizon_days)),
+    }
+}
@@ -0,0 +1,144 @@
+#![cfg(test)]
+
+use actix_web::{test, App, HttpServer, Error};
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::web::{self, Data};
+use actix_http::Request;
+use crate::fake_upstream::*;
+use crate::web::action::*;
+use crate::web::AppState;
+use crate::web::metrics::Metrics;
+use crate::web::health::ProviderHealth;
+use weather_core::warning::WarningCode;
+use weather_core::transport::ReqwestTransport;
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use chrono::{Duration, Utc};
+
+const APIXU_KEY: &str = "apixu_key";
+
+fn yahoo_credentials() -> YahooCredentials {
+    YahooCredentials {
+        app_id: String::from("app_id"),
+        user_key: String::from("user_key"),
+        secret: String::from("secret"),
+    }
+}
+
+/// Starts the fake upstream on a free port, returns its base url.
+fn start_fake_upstream(config: FakeUpstreamConfig) -> String {
+    let server = HttpServer::new(move || App::new().configure(configure(config.clone())))
+        .workers(1)
+        .bind("127.0.0.1:0")
+        .unwrap();
+    let address = server.addrs()[0];
+
+    actix_web::rt::spawn(server.run());
+
+    format!("http://{}", address)
+}
+
+/// The service talking over real HTTP to the fake upstream, signing Yahoo requests with `yahoo_secret`.
+async fn create_app(config: FakeUpstreamConfig, yahoo_secret: &str) -> impl Service<Request, Response = ServiceResponse, Error = Error> {
+    let base_uri = start_fake_upstream(config);
+    let credentials = yahoo_credentials();
+
+    let conf: HashMap<String, String> = vec![
+        ("apixu_key", APIXU_KEY.to_owned()),
+        ("apixu_base_uri", format!("{}/v1", base_uri)),
+        ("yahoo_app_id", credentials.app_id),
+        ("yahoo_secret", yahoo_secret.to_owned()),
+        ("yahoo_user_key", credentials.user_key),
+        ("yahoo_base_uri", format!("{}/forecastrss", base_uri)),
+    ].into_iter().map(|(name, value)| (name.to_owned(), value)).collect();
+
+    test::init_service(
+        App::new()
+            .app_data(Data::new(AppState { config: Arc::new(Mutex::new(conf)), geocoder: None, transport: Arc::new(ReqwestTransport::default()), metrics: Arc::new(Metrics::new()), health: Arc::new(ProviderHealth::new()) }))
+            .route("/weather/{location}/week", web::get().to(on_week::handle))
+            .route("/weather/{location}/range", web::get().to(on_range::handle))
+    ).await
+}
+
+fn strict_config() -> FakeUpstreamConfig {
+    FakeUpstreamConfig::default()
+        .with_apixu_key(APIXU_KEY)
+        .with_yahoo_credentials(yahoo_credentials())
+}
+
+async fn get_week(config: FakeUpstreamConfig, yahoo_secret: &str, location: &str) -> ForecastUserResponse {
+    let app = create_app(config, yahoo_secret).await;
+    let request = test::TestRequest::get().uri(&format!("/weather/{}/week?warnings=structured", location)).to_request();
+
+    test::call_and_read_body_json(&app, request).await
+}
+
+fn warnings(warnings: &WarningList) -> Vec<(WarningCode, Option<String>)> {
+    match warnings {
+        WarningList::Structured(warnings) => warnings.iter().map(|warning| (warning.code, warning.provider.clone())).collect(),
+        WarningList::Legacy(_) => panic!("Structured warnings expected"),
+    }
+}
+
+#[actix_rt::test]
+async fn test_fake_upstream_serve_both_providers() {
+    let response = get_week(strict_config(), "secret", "Moscow").await;
+
+    assert!(response.ok);
+    assert_eq!(response.forecast.unwrap().get_temperature_at(Utc::now().format("%Y-%m-%d").to_string()), Some(&10.5));
+    assert!(warnings(&response.warnings).is_empty());
+}
+
+#[actix_rt::test]
+async fn test_fake_upstream_reject_invalid_oauth_signature() {
+    let response = get_week(strict_config(), "wrong_secret", "Moscow").await;
+
+    assert!(response.ok);
+    assert_eq!(warnings(&response.warnings), vec![(WarningCode::RequestError, Some(String::from("Yahoo")))]);
+}
+
+#[actix_rt::test]
+async fn test_fake_upstream_unknown_location() {
+    let config = strict_config()
+        .apixu("Atlantis", Scenario::UnknownLocation)
+        .yahoo("Atlantis", Scenario::UnknownLocation);
+
+    let response = get_week(config, "secret", "Atlantis").await;
+
+    assert!(!response.ok);
+    assert_eq!(warnings(&response.warnings).len(), 2);
+}
+
+#[actix_rt::test]
+async fn test_fake_upstream_server_error_and_invalid_json() {
+    let config = strict_config()
+        .apixu("Paris", Scenario::ServerError)
+        .yahoo("Paris", Scenario::InvalidJson);
+
+    let response = get_week(config, "secret", "Paris").await;
+
+    assert!(!response.ok);
+    assert_eq!(warnings(&response.warnings).len(), 2);
+}
+
+#[actix_rt::test]
+async fn test_fake_upstream_slow_response() {
+    let response = get_week(strict_config().yahoo("Moscow", Scenario::Slow { delay_ms: 100 }), "secret", "Moscow").await;
+
+    assert!(response.ok);
+    assert!(warnings(&response.warnings).is_empty());
+}
+
+#[actix_rt::test]
+async fn test_fake_upstream_truncated_horizon() {
+    let app = create_app(strict_config().apixu("Oslo", Scenario::TruncatedHorizon { days: 2 }), "secret").await;
+
+    let from = Utc::now().format("%Y-%m-%d");
+    let to = (Utc::now() + Duration::days(3)).format("%Y-%m-%d");
+    let request = test::TestRequest::get().uri(&format!("/weather/Oslo/range?from={}&to={}&warnings=structured", from, to)).to_request();
+    let response: RangeUserResponse = test::call_and_read_body_json(&app, request).await;
+
+    assert!(response.ok);
+    assert_eq!(response.coverage.values().filter(|providers| providers.len() == 2).count(), 2);
+    assert_eq!(warnings(&response.warnings), vec![(WarningCode::PartialCoverage, Some(String::from("Apixu")))]);
+}
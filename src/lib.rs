@@ -2,16 +2,22 @@ extern crate actix_web;
 extern crate futures;
 extern crate serde_json;
 #[macro_use] extern crate serde_derive;
-extern crate actix;
-#[macro_use] extern crate failure;
-extern crate rand;
-extern crate crypto;
-extern crate base64;
-#[macro_use] extern crate url;
+extern crate thiserror;
 extern crate chrono;
 extern crate chrono_tz;
-extern crate time;
-#[macro_use] extern crate log;
+extern crate weather_core;
+extern crate prometheus;
+extern crate async_trait;
+extern crate uuid;
+extern crate tracing;
+extern crate tracing_subscriber;
+extern crate tracing_opentelemetry;
+extern crate opentelemetry;
+extern crate opentelemetry_sdk;
+extern crate opentelemetry_otlp;
+extern crate utoipa;
+extern crate csv;
+extern crate quick_xml;
 
 pub mod web;
-pub mod app;
\ No newline at end of file
+pub mod fake_upstream;
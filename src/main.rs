@@ -1,38 +1,75 @@
 extern crate actix_web;
-extern crate env_logger;
 extern crate config;
 
-use actix_web::{server, App, middleware};
-use actix_web::http::Method;
-use std::env;
+use actix_web::{App, HttpServer};
+use actix_web::middleware::from_fn;
+use actix_web::web::{self, Data};
+use weather_service::web::action;
 use weather_service::web::AppState;
+use weather_service::web::metrics::{self, Metrics};
+use weather_service::web::health::ProviderHealth;
+use weather_service::web::{logging, openapi, request_id, telemetry};
+use weather_core::geocoder::Geocoder;
+use weather_core::transport::ReqwestTransport;
 use std::sync::Arc;
 use std::collections::HashMap;
 use std::sync::Mutex;
 use config::*;
 
-fn main() {
-    env::set_var("RUST_LOG", "actix_web=debug");
-    env::set_var("RUST_LOG", "weather_service=info");
-
-    env_logger::init();
-
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
     let mut settings = Config::default();
     settings.merge(File::with_name("cfg/config.json")).unwrap();
 
-    let conf = Arc::new(Mutex::new(settings.try_into::<HashMap<String, String>>().unwrap()));
-
-    server::new(move||
-        App::with_state(AppState { config: conf.clone() })
-            .middleware(middleware::Logger::default())
-            .resource("/weather/{location}/on/{date}", |r| {
-                r.method(Method::GET).a(weather_service::web::action::on_date::handle);
-            })
-            .resource("/weather/{location}/week", |r| {
-                r.method(Method::GET).a(weather_service::web::action::on_week::handle);
-            })
+    let conf = settings.try_into::<HashMap<String, String>>().unwrap();
+
+    let tracer_provider = telemetry::tracer_provider_from_config(&conf).unwrap();
+    logging::init(tracer_provider.as_ref().map(telemetry::tracer));
+
+    let geocoder = conf.get("geonames_path").and_then(|path| match Geocoder::load(path) {
+        Ok(geocoder) => {
+            tracing::info!(path = %path, places = geocoder.len(), "gazetteer loaded");
+            Some(Arc::new(geocoder))
+        }
+        Err(e) => {
+            tracing::warn!(path = %path, error = %e, "gazetteer not loaded, city names go to providers as is");
+            None
+        }
+    });
+
+    let state = Data::new(AppState {
+        config: Arc::new(Mutex::new(conf)),
+        geocoder,
+        transport: Arc::new(ReqwestTransport::default()),
+        metrics: Arc::new(Metrics::new()),
+        health: Arc::new(ProviderHealth::new()),
+    });
+
+    HttpServer::new(move ||
+        App::new()
+            .app_data(state.clone())
+            .wrap(from_fn(metrics::track_requests))
+            .wrap(from_fn(request_id::assign_request_id))
+            .route("/metrics", web::get().to(metrics::handle))
+            .route("/openapi.json", web::get().to(openapi::handle))
+            .route("/docs", web::get().to(openapi::docs))
+            .route("/healthz", web::get().to(action::health::healthz))
+            .route("/readyz", web::get().to(action::health::readyz))
+            .route("/providers", web::get().to(action::providers::handle))
+            .configure(action::routes)
+            .service(web::scope("/v1").configure(action::routes))
+            .service(web::scope("/v2").configure(action::v2::routes))
     )
-        .bind("127.0.0.1:8088")
-        .unwrap()
-        .run();
+        .bind("127.0.0.1:8088")?
+        .run()
+        .await?;
+
+    // Sends the spans still waiting for their batch.
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            tracing::warn!(error = %e, "span export not finished");
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,315 @@
+use actix_web::{HttpRequest, HttpResponse, Error};
+use actix_web::web::{Data, Json};
+use futures::StreamExt;
+use futures::stream;
+use tracing::Instrument;
+use utoipa::ToSchema;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use chrono::NaiveDate;
+use crate::web::AppState;
+use crate::web::request_id::request_id;
+use crate::web::metrics::CacheStatus;
+use weather_core::Date;
+use weather_core::date;
+use weather_core::date::Period;
+use weather_core::forecast::{Forecast, ForecastAggregate};
+use weather_core::geocoder::Candidate;
+use weather_core::location::{Location, LocationError};
+use weather_core::warning::Warning;
+
+use actix_web::error;
+use crate::web::action::{WarningList, WarningFormat, QueryError, create_aggregate_manager_with_cache, resolve_location, location_timezone};
+use crate::web::action::{on_week, on_range};
+
+/// Location and dates of a query along with what else is known about it, or the result of a failed query.
+type PreparedQuery<T> = Result<(Location, Period, T), Box<BatchQueryResult>>;
+/// Providers asked for a query and warnings for the skipped ones.
+type QueryProviders = (Vec<String>, Vec<Warning>);
+
+const MAX_QUERIES: usize = 100;
+pub const MAX_BODY_SIZE: usize = 256 * 1024;
+const DEFAULT_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchQuery {
+    /// Key of the query result, generated from the query when missing.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub location: String,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub range: Option<BatchRange>,
+    #[serde(default)]
+    pub week: Option<BatchWeek>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchRange {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchWeek {
+    #[serde(default)]
+    pub days: Option<u32>,
+}
+
+impl BatchQuery {
+    fn key(&self) -> String {
+        if let Some(ref id) = self.id {
+            return id.clone();
+        }
+
+        let location = match self.country {
+            Some(ref country) => format!("{}, {}", self.location, country),
+            None => self.location.clone(),
+        };
+
+        match (&self.date, &self.range, &self.week) {
+            (Some(date), _, _) => format!("{}/on/{}", location, date),
+            (_, Some(range), _) => format!("{}/range/{}/{}", location, range.from, range.to),
+            (_, _, Some(BatchWeek { days: Some(days) })) => format!("{}/week/{}", location, days),
+            _ => format!("{}/week", location),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchQueryResult {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<Candidate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub from: Option<Date>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub to: Option<Date>,
+    pub forecast: Option<Forecast>,
+    pub warnings: WarningList,
+}
+
+impl BatchQueryResult {
+    fn failed(error: String, candidates: Vec<Candidate>, warning_format: WarningFormat) -> Box<Self> {
+        Box::new(BatchQueryResult {
+            ok: false,
+            error: Some(error),
+            candidates,
+            from: None,
+            to: None,
+            forecast: None,
+            warnings: WarningList::new(&[], warning_format, None),
+        })
+    }
+
+    fn from_aggregate(aggregate: &ForecastAggregate, period: &Period, warning_format: WarningFormat, request_id: Option<&str>) -> Self {
+        let (from, to) = period.bounds();
+        let aggregate_result = aggregate.calculate_average_forecast();
+
+        BatchQueryResult {
+            ok: aggregate_result.is_some(),
+            error: None,
+            candidates: Vec::new(),
+            from: Some(date::format_date(&from)),
+            to: Some(date::format_date(&to)),
+            forecast: aggregate_result,
+            warnings: WarningList::new(aggregate.get_warnings(), warning_format, request_id),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchUserResponse {
+    pub ok: bool,
+    pub results: BTreeMap<String, BatchQueryResult>,
+}
+
+/// Runs every query of the batch, each distinct location is asked from providers only once
+/// and no more than `batch_concurrency` locations are in flight at the same time.
+///
+/// Body size is limited by the `JsonConfig` of the route, see `MAX_BODY_SIZE`.
+#[utoipa::path(
+    post,
+    path = "/weather/batch",
+    operation_id = "batch",
+    tag = "weather",
+    params(
+        ("warnings" = Option<String>, Query, description = "`structured` renders warnings as objects with `code`, `provider`, `date`, `message` and `retryable`"),
+    ),
+    request_body(content = Vec<BatchQuery>, description = "1 to 100 queries, each with exactly one of `date`, `range` and `week`"),
+    responses(
+        (status = 200, description = "Results by query key, failed queries have `ok` false and `error`", body = BatchUserResponse),
+        (status = 400, description = "Empty or too long batch, queries with the same key, malformed body", body = String, content_type = "text/plain"),
+    )
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "batch", queries = queries.len() as i64))]
+pub async fn handle(req: HttpRequest, state: Data<AppState>, queries: Json<Vec<BatchQuery>>) -> Result<HttpResponse, Error> {
+    let queries = queries.into_inner();
+
+    if queries.is_empty() || queries.len() > MAX_QUERIES {
+        return Err(error::ErrorBadRequest(QueryError::InvalidParam {
+            name: String::from("queries"),
+            value: queries.len().to_string(),
+        }));
+    }
+
+    // Results are keyed, a second query with the same key would silently replace the first one.
+    let mut keys: HashSet<String> = HashSet::new();
+    if let Some(duplicate) = queries.iter().map(BatchQuery::key).find(|key| !keys.insert(key.clone())) {
+        return Err(error::ErrorBadRequest(QueryError::InvalidParam {
+            name: String::from("id"),
+            value: duplicate,
+        }));
+    }
+
+    let warning_format = WarningFormat::from_request(&req);
+    let request_id = request_id(&req);
+
+    let prepared: Vec<(String, PreparedQuery<NaiveDate>)> = queries
+        .iter()
+        .map(|query| (query.key(), prepare(&state, query, warning_format)))
+        .collect();
+
+    let unique_locations: BTreeMap<String, Location> = prepared
+        .iter()
+        .filter_map(|(_, prepared)| prepared.as_ref().ok())
+        .map(|(location, _, _)| (location.key(), location.clone()))
+        .collect();
+
+    let concurrency = state.config.lock().unwrap()
+        .get("batch_concurrency")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_CONCURRENCY);
+
+    let aggregate_manager = create_aggregate_manager_with_cache(&state, CacheStatus::Miss);
+
+    // Providers unable to answer a query are left out of its result with a warning, the location is still fetched once.
+    let prepared: Vec<(String, PreparedQuery<QueryProviders>)> = prepared
+        .into_iter()
+        .map(|(key, prepared)| (key, prepared.map(|(location, period, today)| {
+            let provider_names = aggregate_manager.get_forecast_provider_names(&location, &period, &today);
+            let skipped = aggregate_manager.get_forecast_skip_warnings(&location, &period, &today);
+            (location, period, (provider_names, skipped))
+        })))
+        .collect();
+
+    // The first query for a location misses and has it fetched, the next ones are served from the aggregate fetched for it.
+    let mut fetched: HashSet<String> = HashSet::new();
+    let cache_statuses: HashMap<String, CacheStatus> = prepared
+        .iter()
+        .filter_map(|(key, prepared)| prepared.as_ref().ok().map(|(location, _, _)| (key, location)))
+        .map(|(key, location)| (key.clone(), match fetched.insert(location.key()) {
+            true => CacheStatus::Miss,
+            false => CacheStatus::Hit,
+        }))
+        .collect();
+
+    let cache_hits = cache_statuses.values().filter(|status| **status == CacheStatus::Hit).count();
+    state.metrics.observe_cache_lookups("batch_location", cache_hits, cache_statuses.len() - cache_hits);
+
+    let mut queries_per_location: HashMap<String, usize> = HashMap::new();
+    for (location, _, _) in prepared.iter().filter_map(|(_, prepared)| prepared.as_ref().ok()) {
+        *queries_per_location.entry(location.key()).or_insert(0) += 1;
+    }
+
+    let aggregates: HashMap<String, ForecastAggregate> = stream::iter(unique_locations)
+        .map(|(key, location)| {
+            let aggregate_manager = &aggregate_manager;
+            let span = tracing::info_span!(
+                "batch_location",
+                location = %location,
+                queries = queries_per_location[&key] as i64,
+            );
+
+            async move { (key, aggregate_manager.get_forecast_aggregate(&location).await) }.instrument(span)
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+
+    let results: BTreeMap<String, BatchQueryResult> = prepared
+        .into_iter()
+        .map(|(key, prepared)| (key.clone(), match prepared {
+            Ok((location, period, (provider_names, skipped))) => {
+                let span = tracing::info_span!(
+                    "batch_query",
+                    key = %key,
+                    location = %location,
+                    cache = cache_statuses[&key].as_str(),
+                    warnings = tracing::field::Empty,
+                );
+                let _entered = span.enter();
+
+                let aggregate = aggregates[&location.key()]
+                    .clone()
+                    .retain_providers(&provider_names)
+                    .filter_by_period(&period)
+                    .with_warning_collection(skipped);
+                state.metrics.observe_aggregate("forecast", aggregate.provider_count());
+                span.record("warnings", aggregate.get_warnings().len() as i64);
+
+                BatchQueryResult::from_aggregate(&aggregate, &period, warning_format, request_id.as_deref())
+            }
+            Err(failed) => *failed,
+        }))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(BatchUserResponse {
+        ok: results.values().all(|result| result.ok),
+        results,
+    }))
+}
+
+/// Resolves the query location, the dates it asks for and today at the location,
+/// exactly one of `date`, `range` or `week` is expected.
+fn prepare(state: &AppState, query: &BatchQuery, warning_format: WarningFormat) -> PreparedQuery<NaiveDate> {
+    let mut params: HashMap<String, String> = HashMap::new();
+    if let Some(ref country) = query.country {
+        params.insert(String::from("country"), country.clone());
+    }
+
+    let location = resolve_location(state, &query.location, &params).map_err(|e| {
+        let error = e.to_string();
+
+        match e {
+            LocationError::AmbiguousLocation { candidates, .. } | LocationError::UnknownLocation { candidates, .. } => BatchQueryResult::failed(error, candidates, warning_format),
+            _ => BatchQueryResult::failed(error, Vec::new(), warning_format),
+        }
+    })?;
+
+    let today = date::today_in(location_timezone(state, &location));
+
+    let invalid_period = |error: String| BatchQueryResult::failed(error, Vec::new(), warning_format);
+
+    let period = match (&query.date, &query.range, &query.week) {
+        (Some(date_spec), None, None) => date::resolve_date(date_spec, &today)
+            .map(Period::Date)
+            .map_err(|e| invalid_period(e.to_string()))?,
+        (None, Some(range), None) => {
+            let parse = |value: &str| date::parse_date(value).ok_or(QueryError::InvalidParam {
+                name: String::from("range"),
+                value: value.to_owned(),
+            });
+
+            let (from, to) = parse(&range.from)
+                .and_then(|from| parse(&range.to).map(|to| (from, to)))
+                .and_then(|(from, to)| on_range::check_range(from, to))
+                .map_err(|e| invalid_period(e.to_string()))?;
+
+            Period::Range { from, to }
+        }
+        (None, None, Some(week)) => Period::Week {
+            today,
+            days: on_week::check_days(week.days.unwrap_or(on_week::DEFAULT_DAYS)).map_err(|e| invalid_period(e.to_string()))?,
+        },
+        _ => return Err(invalid_period(String::from("Exactly one of date, range or week expected !"))),
+    };
+
+    Ok((location, period, today))
+}
@@ -0,0 +1,40 @@
+use actix_web::{HttpRequest, HttpResponse, Error};
+use actix_web::web::Data;
+use chrono::Utc;
+use crate::web::{ical, render, AppState};
+use crate::web::metrics::CacheStatus;
+use tracing::field;
+use crate::web::action::{LocationErrorResponses, LocationParams};
+use crate::web::action::on_week;
+
+/// Forecast for `days` days starting today as an iCalendar feed, one all-day event per date.
+#[utoipa::path(
+    get,
+    path = "/weather/{location}/forecast.ics",
+    operation_id = "forecast_ics",
+    tag = "weather",
+    params(
+        ("location" = String, Path, description = "City (`Moscow`, `Paris, FR`), coordinates (`55.75,37.61`), `zip:...` or `iata:...`"),
+        ("days" = Option<u32>, Query, description = "Period length, from 1 to 7, 5 by default"),
+        LocationParams,
+    ),
+    responses(
+        (status = 200, description = "Calendar with an event per forecast date, empty when no provider answered", content(
+            (String = "text/calendar"),
+        )),
+        LocationErrorResponses,
+    )
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "forecast_ics", location = field::Empty, cache = CacheStatus::Bypass.as_str()))]
+pub async fn handle(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, Error> {
+    let week = match on_week::week_aggregate(&req, &state).await {
+        Ok(week) => week,
+        Err(response) => return Ok(response),
+    };
+
+    let rows = render::forecast_rows(&week.aggregate);
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(ical::calendar(&week.location, &rows, Utc::now())))
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use actix_web::HttpResponse;
+use actix_web::rt;
+use actix_web::web::Data;
+use weather_core::location::Location;
+use crate::web::AppState;
+
+use crate::web::action::{HealthUserResponse, ReadinessUserResponse, create_aggregate_manager, resolve_location};
+
+const DEFAULT_MIN_HEALTHY_PROVIDERS: usize = 1;
+const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+const DEFAULT_PROBE_INTERVAL_SECONDS: u64 = 60;
+
+/// Liveness: the process is up and serving requests.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "service",
+    responses((status = 200, description = "Always `{\"ok\": true}`", body = HealthUserResponse))
+)]
+pub async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(HealthUserResponse { ok: true })
+}
+
+/// Readiness: 503 while fewer than `min_healthy_providers` providers are healthy.
+///
+/// With `readiness_probe_location` configured, the current conditions for it are requested from every provider
+/// (at most once per `readiness_probe_interval_seconds`), otherwise only the outcomes of real requests count.
+/// The probe runs in the background: the answer comes from the outcomes recorded so far, without waiting for providers.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "service",
+    responses(
+        (status = 200, description = "Enough healthy providers", body = ReadinessUserResponse),
+        (status = 503, description = "Fewer than `min_healthy_providers` healthy providers", body = ReadinessUserResponse),
+    )
+)]
+pub async fn readyz(state: Data<AppState>) -> HttpResponse {
+    let min_healthy_providers = config_value(&state, "min_healthy_providers", DEFAULT_MIN_HEALTHY_PROVIDERS);
+    let failure_threshold = config_value(&state, "provider_failure_threshold", DEFAULT_FAILURE_THRESHOLD);
+    let probe_interval = Duration::from_secs(config_value(&state, "readiness_probe_interval_seconds", DEFAULT_PROBE_INTERVAL_SECONDS));
+    let probe_location = state.config.lock().unwrap().get("readiness_probe_location").cloned();
+
+    if let Some(location) = probe_location.and_then(|location| resolve_location(&state, &location, &HashMap::new()).ok()) {
+        if state.health.start_probe(probe_interval) {
+            rt::spawn(probe(state.clone(), location));
+        }
+    }
+
+    let provider_names: Vec<String> = create_aggregate_manager(&state).get_capabilities().into_iter().map(|capabilities| capabilities.name).collect();
+    let providers = state.health.statuses(&provider_names, failure_threshold);
+    let healthy_providers = providers.iter().filter(|status| status.healthy).count();
+
+    let response = ReadinessUserResponse {
+        ok: healthy_providers >= min_healthy_providers,
+        healthy_providers,
+        min_healthy_providers,
+        providers,
+    };
+
+    match response.ok {
+        true => HttpResponse::Ok().json(response),
+        false => HttpResponse::ServiceUnavailable().json(response),
+    }
+}
+
+/// Asks every provider for the current conditions at `location`, `InstrumentedProvider` records the outcomes.
+async fn probe(state: Data<AppState>, location: Location) {
+    create_aggregate_manager(&state).get_current_conditions_aggregate(&location).await;
+}
+
+fn config_value<T: FromStr>(state: &AppState, name: &str, default: T) -> T {
+    state.config.lock().unwrap()
+        .get(name)
+        .and_then(|value| value.parse::<T>().ok())
+        .unwrap_or(default)
+}
@@ -1,11 +1,297 @@
-use crate::app::forecast::Forecast;
+use actix_web::HttpRequest;
+use actix_web::HttpResponse;
+use actix_web::http::StatusCode;
+use actix_web::web::{self, JsonConfig, Query, ServiceConfig};
+use weather_core::forecast::Forecast;
+use weather_core::current::{AverageConditions, CurrentConditions};
+use weather_core::warning::Warning;
+use weather_core::provider::capabilities::ProviderCapabilities;
+use weather_core::location::{Location, LocationError};
+use weather_core::geocoder::Candidate;
+use crate::web::{request_id, AppState};
+use crate::web::metrics::{CacheStatus, InstrumentedProvider};
+use crate::web::health::ProviderStatus;
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, HashMap};
+use weather_core::Date;
+use weather_core::WeatherAggregateManager;
+use weather_core::provider::apixu;
+use weather_core::provider::yahoo;
+use thiserror::Error;
+use utoipa::{IntoParams, IntoResponses, PartialSchema, ToSchema};
+use utoipa::openapi::{Content, Ref, RefOr, ResponseBuilder, ResponsesBuilder};
+use utoipa::openapi::response::Response;
 
 pub mod on_week;
 pub mod on_date;
+pub mod on_range;
+pub mod batch;
+pub mod on_now;
+pub mod providers;
+pub mod health;
+pub mod v2;
+pub mod calendar;
+pub mod week_chart;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Error)]
+pub enum QueryError {
+    #[error("Invalid {} param: {} !", .name, .value)]
+    InvalidParam {
+        name: String,
+        value: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ForecastUserResponse {
     pub ok: bool,
+    /// Resolved date for the `on/{date}` requests (`tomorrow` -> `2019-03-27`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub date: Option<Date>,
+    pub forecast: Option<Forecast>,
+    pub warnings: WarningList,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RangeUserResponse {
+    pub ok: bool,
+    #[schema(value_type = String)]
+    pub from: Date,
+    #[schema(value_type = String)]
+    pub to: Date,
     pub forecast: Option<Forecast>,
-    pub warnings: Vec<String>
-}
\ No newline at end of file
+    #[schema(value_type = BTreeMap<String, Vec<String>>)]
+    pub coverage: BTreeMap<Date, Vec<String>>,
+    pub warnings: WarningList,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CurrentUserResponse {
+    pub ok: bool,
+    pub current: Option<AverageConditions>,
+    pub observations: Vec<CurrentConditions>,
+    pub warnings: WarningList,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProvidersUserResponse {
+    pub ok: bool,
+    pub providers: Vec<ProviderCapabilities>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HealthUserResponse {
+    pub ok: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessUserResponse {
+    pub ok: bool,
+    pub healthy_providers: usize,
+    pub min_healthy_providers: usize,
+    pub providers: Vec<ProviderStatus>,
+}
+
+/// Warnings as they are rendered to the client: plain strings as v1 clients know them or, on request, structured objects.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum WarningList {
+    Structured(Vec<Warning>),
+    Legacy(Vec<String>),
+}
+
+impl WarningList {
+    /// Structured warnings carry `request_id`, legacy strings stay as v1 clients know them.
+    pub fn new(warnings: &[Warning], format: WarningFormat, request_id: Option<&str>) -> Self {
+        match format {
+            WarningFormat::Structured => WarningList::Structured(
+                warnings.iter().map(|w| w.clone().with_request_id(request_id.map(String::from))).collect()
+            ),
+            WarningFormat::Legacy => WarningList::Legacy(warnings.iter().map(|w| w.to_legacy_string()).collect()),
+        }
+    }
+
+    /// Warnings in the format `req` asks for, structured ones carry its request id.
+    pub fn from_request(warnings: &[Warning], req: &HttpRequest) -> Self {
+        WarningList::new(warnings, WarningFormat::from_request(req), request_id::request_id(req).as_deref())
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            WarningList::Structured(warnings) => warnings.len(),
+            WarningList::Legacy(warnings) => warnings.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WarningFormat {
+    Structured,
+    Legacy,
+}
+
+impl WarningFormat {
+    /// Strings by default, so deployed v1 clients keep parsing them; `?warnings=structured` asks for objects.
+    pub fn from_request(req: &HttpRequest) -> Self {
+        match query_params(req).get("warnings").map(|v| v.as_str()) {
+            Some("structured") => WarningFormat::Structured,
+            _ => WarningFormat::Legacy,
+        }
+    }
+}
+
+/// Query params shared by the `/weather/{location}/...` routes, for the API description only:
+/// handlers read them with `query_params`.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LocationParams {
+    /// Latitude, with `lon` the path location is ignored.
+    pub lat: Option<f64>,
+    /// Longitude, with `lat` the path location is ignored.
+    pub lon: Option<f64>,
+    /// Gazetteer place id, one of the `candidates` of a 300 or 404 answer.
+    pub id: Option<u64>,
+    /// ISO country code of the city (`US`).
+    pub country: Option<String>,
+    /// `structured` renders warnings as objects with `code`, `provider`, `date`, `message` and `retryable`.
+    pub warnings: Option<String>,
+}
+
+/// Forecast routes of the v1 API, mounted both at the root and in the `/v1` scope.
+pub fn routes(cfg: &mut ServiceConfig) {
+    cfg.service(
+        web::resource("/weather/batch")
+            .app_data(JsonConfig::default().limit(batch::MAX_BODY_SIZE))
+            .route(web::post().to(batch::handle))
+    )
+        .route("/weather/{location}/on/{date}", web::get().to(on_date::handle))
+        .route("/weather/{location}/week", web::get().to(on_week::handle))
+        .route("/weather/{location}/range", web::get().to(on_range::handle))
+        .route("/weather/{location}/now", web::get().to(on_now::handle))
+        .route("/weather/{location}/forecast.ics", web::get().to(calendar::handle))
+        .route("/weather/{location}/week.svg", web::get().to(week_chart::handle));
+}
+
+/// Query string params, malformed query strings read as empty.
+pub fn query_params(req: &HttpRequest) -> HashMap<String, String> {
+    Query::<HashMap<String, String>>::from_query(req.query_string())
+        .map(|query| query.into_inner())
+        .unwrap_or_default()
+}
+
+/// Providers for one request, built from the config and sharing the state's transport.
+pub fn create_aggregate_manager(state: &AppState) -> WeatherAggregateManager {
+    create_aggregate_manager_with_cache(state, CacheStatus::Bypass)
+}
+
+/// Same as `create_aggregate_manager`, provider calls are recorded with `cache`.
+pub fn create_aggregate_manager_with_cache(state: &AppState, cache: CacheStatus) -> WeatherAggregateManager {
+    let config = state.config.lock().unwrap();
+
+    let mut apixu = apixu::ApixuProvider::new(state.transport.clone(), config.get("apixu_key").unwrap().clone());
+    if let Some(base_uri) = config.get("apixu_base_uri") {
+        apixu = apixu.with_base_uri(base_uri.clone());
+    }
+
+    let mut yahoo = yahoo::YahooProvider::new(
+        state.transport.clone(),
+        config.get("yahoo_app_id").unwrap().clone(),
+        config.get("yahoo_secret").unwrap().clone(),
+        config.get("yahoo_user_key").unwrap().clone(),
+    );
+    if let Some(base_uri) = config.get("yahoo_base_uri") {
+        yahoo = yahoo.with_base_uri(base_uri.clone());
+    }
+
+    WeatherAggregateManager::new(vec![
+        Box::new(InstrumentedProvider::new(Box::new(apixu), state.metrics.clone(), state.health.clone(), cache)),
+        Box::new(InstrumentedProvider::new(Box::new(yahoo), state.metrics.clone(), state.health.clone(), cache)),
+    ])
+}
+
+/// Location from the path (or `?lat=&lon=`), city names are resolved through the gazetteer when it is loaded.
+///
+/// `?country=` narrows the city name down to a country, `?id=` picks a gazetteer place directly.
+pub fn location_from_request(req: &HttpRequest, state: &AppState) -> Result<Location, LocationError> {
+    let location = resolve_location(state, req.match_info().get("location").unwrap_or(""), &query_params(req))?;
+    request_id::record_location(req, &location.to_string());
+
+    Ok(location)
+}
+
+/// Same as `location_from_request`, for locations coming from elsewhere than the request path.
+pub fn resolve_location(state: &AppState, path: &str, params: &HashMap<String, String>) -> Result<Location, LocationError> {
+    let lat = params.get("lat").map(|v| v.as_str());
+    let lon = params.get("lon").map(|v| v.as_str());
+
+    let geocoder = match state.geocoder {
+        Some(ref geocoder) => geocoder,
+        None => return Location::parse(path, lat, lon),
+    };
+
+    if let Some(id) = params.get("id") {
+        return match id.parse::<u64>() {
+            Ok(id) => Location::from_place_id(geocoder, id),
+            Err(_) => Err(LocationError::InvalidPlaceId { id: id.clone() }),
+        };
+    }
+
+    Location::parse(path, lat, lon)?.canonicalize(geocoder, params.get("country").map(|v| v.as_str()))
+}
+
+pub fn location_timezone(state: &AppState, location: &Location) -> Tz {
+    location.timezone(state.geocoder.as_ref().map(|geocoder| geocoder.as_ref()))
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LocationCandidatesResponse {
+    pub ok: bool,
+    pub error: String,
+    pub candidates: Vec<Candidate>,
+}
+
+/// 300 with candidates for ambiguous locations, 404 with suggestions for unknown ones, 400 for malformed ones.
+pub fn location_error_response(e: LocationError) -> HttpResponse {
+    let error = e.to_string();
+
+    let (status, candidates) = match e {
+        LocationError::AmbiguousLocation { candidates, .. } => (StatusCode::MULTIPLE_CHOICES, candidates),
+        LocationError::UnknownLocation { candidates, .. } => (StatusCode::NOT_FOUND, candidates),
+        _ => (StatusCode::BAD_REQUEST, Vec::new()),
+    };
+
+    HttpResponse::build(status).json(LocationCandidatesResponse {
+        ok: false,
+        error,
+        candidates,
+    })
+}
+
+/// OpenAPI for the answers of `location_error_response` and for malformed query params,
+/// listed in `responses(...)` of every endpoint taking a location.
+pub struct LocationErrorResponses;
+
+impl IntoResponses for LocationErrorResponses {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        let candidates = || Content::new(Some(Ref::from_schema_name(LocationCandidatesResponse::name())));
+
+        ResponsesBuilder::new()
+            .response("300", ResponseBuilder::new()
+                .description("Ambiguous location, places to choose from in `candidates`")
+                .content("application/json", candidates()))
+            .response("400", ResponseBuilder::new()
+                .description("Malformed location or query param")
+                .content("application/json", candidates())
+                .content("text/plain", Content::new(Some(String::schema()))))
+            .response("404", ResponseBuilder::new()
+                .description("Unknown location, similar places in `candidates`")
+                .content("application/json", candidates()))
+            .build()
+            .responses
+    }
+}
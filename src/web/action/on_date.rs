@@ -1,43 +1,62 @@
-use actix_web::HttpRequest;
-use actix_web::FutureResponse;
-use actix_web::HttpResponse;
-use futures::Future;
-use crate::app::provider::apixu;
-use crate::app::provider::yahoo;
-use crate::app::WeatherAggregateManager;
+use actix_web::{HttpRequest, HttpResponse, Error};
+use actix_web::web::Data;
 use crate::web::AppState;
+use crate::web::metrics::CacheStatus;
+use tracing::field;
+use crate::web::render::{self, Format};
+use weather_core::date;
 
 use actix_web::error;
-use crate::web::action::ForecastUserResponse;
+use crate::web::action::{ForecastUserResponse, LocationErrorResponses, LocationParams, WarningList, create_aggregate_manager, location_from_request, location_error_response, location_timezone};
 
-pub fn handle(req: &HttpRequest<AppState>) -> FutureResponse<HttpResponse> {
-    let location = req.match_info().get("location").unwrap();
-    let date = req.match_info().get("date").unwrap();
-
-    let config = req.state().config.lock().unwrap();
-
-    let aggregate_manager = WeatherAggregateManager::new(vec![
-        Box::new(apixu::ApixuProvider::new(config.get("apixu_key").unwrap().clone())),
-        Box::new(yahoo::YahooProvider::new(
-            config.get("yahoo_app_id").unwrap().clone(),
-            config.get("yahoo_secret").unwrap().clone(),
-            config.get("yahoo_user_key").unwrap().clone(),
+/// Forecast for one day, `date` may be relative to today in the location timezone.
+#[utoipa::path(
+    get,
+    path = "/weather/{location}/on/{date}",
+    operation_id = "on_date",
+    tag = "weather",
+    params(
+        ("location" = String, Path, description = "City (`Moscow`, `Paris, FR`), coordinates (`55.75,37.61`), `zip:...` or `iata:...`"),
+        ("date" = String, Path, description = "`2019-03-26`, `today`, `tomorrow`, `+3d`, week day (`saturday`, `sat`)"),
+        ("format" = Option<String>, Query, description = "`json`, `csv`, `xml` or `text`, overrides `Accept`"),
+        LocationParams,
+    ),
+    responses(
+        (status = 200, description = "Forecast averaged over providers, `date` is the resolved date", content(
+            (ForecastUserResponse = "application/json"),
+            (String = "text/csv"),
+            (String = "application/xml"),
+            (String = "text/plain"),
         )),
-    ]);
-
-    Box::new(
-        aggregate_manager
-            .get_forecast_aggregate_at(date.to_owned(), location)
-            .and_then(|forecast_aggregate| {
-                let aggregate_result = forecast_aggregate.calculate_average_forecast();
-                Ok(HttpResponse::Ok().json(ForecastUserResponse {
-                    ok: aggregate_result.is_some(),
-                    forecast: aggregate_result,
-                    warnings: forecast_aggregate.get_warnings().clone()
-                }))
-
-            })
-            .map_err(|e| error::ErrorBadRequest(e))
+        LocationErrorResponses,
     )
-}
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "on_date", location = field::Empty, cache = CacheStatus::Bypass.as_str()))]
+pub async fn handle(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, Error> {
+    let location = match location_from_request(&req, &state) {
+        Ok(location) => location,
+        Err(e) => return Ok(location_error_response(e)),
+    };
+
+    let today = date::today_in(location_timezone(&state, &location));
+
+    let date = date::resolve_date(req.match_info().get("date").unwrap_or(""), &today)
+        .map_err(error::ErrorBadRequest)?;
 
+    let format = Format::from_request(&req).map_err(error::ErrorBadRequest)?;
+
+    let forecast_aggregate = create_aggregate_manager(&state)
+        .get_forecast_aggregate_at(&location, date, today)
+        .await;
+
+    state.metrics.observe_aggregate("forecast", forecast_aggregate.provider_count());
+
+    let aggregate_result = forecast_aggregate.calculate_average_forecast();
+
+    Ok(render::forecast(format, &ForecastUserResponse {
+        ok: aggregate_result.is_some(),
+        date: Some(date::format_date(&date)),
+        forecast: aggregate_result,
+        warnings: WarningList::from_request(forecast_aggregate.get_warnings(), &req),
+    }, &location.to_string(), &forecast_aggregate))
+}
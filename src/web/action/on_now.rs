@@ -0,0 +1,54 @@
+use actix_web::{HttpRequest, HttpResponse, Error};
+use actix_web::web::Data;
+use chrono::Utc;
+use crate::web::AppState;
+use crate::web::metrics::CacheStatus;
+use tracing::field;
+
+use crate::web::action::{CurrentUserResponse, LocationErrorResponses, LocationParams, WarningList, create_aggregate_manager, location_from_request, location_error_response};
+
+const DEFAULT_MAX_OBSERVATION_AGE_MINUTES: i64 = 180;
+
+/// Current conditions averaged over the providers, stale observations are left out with a warning.
+#[utoipa::path(
+    get,
+    path = "/weather/{location}/now",
+    operation_id = "now",
+    tag = "weather",
+    params(
+        ("location" = String, Path, description = "City (`Moscow`, `Paris, FR`), coordinates (`55.75,37.61`), `zip:...` or `iata:...`"),
+        LocationParams,
+    ),
+    responses(
+        (status = 200, description = "Averaged and per provider conditions", body = CurrentUserResponse),
+        LocationErrorResponses,
+    )
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "now", location = field::Empty, cache = CacheStatus::Bypass.as_str()))]
+pub async fn handle(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, Error> {
+    let location = match location_from_request(&req, &state) {
+        Ok(location) => location,
+        Err(e) => return Ok(location_error_response(e)),
+    };
+
+    let max_observation_age = state.config.lock().unwrap()
+        .get("max_observation_age_minutes")
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_MAX_OBSERVATION_AGE_MINUTES);
+
+    let current_aggregate = create_aggregate_manager(&state)
+        .get_current_conditions_aggregate(&location)
+        .await
+        .check_staleness(Utc::now().timestamp(), max_observation_age);
+
+    state.metrics.observe_aggregate("current", current_aggregate.get_observations().len());
+
+    let aggregate_result = current_aggregate.calculate_average_conditions();
+
+    Ok(HttpResponse::Ok().json(CurrentUserResponse {
+        ok: aggregate_result.is_some(),
+        current: aggregate_result,
+        observations: current_aggregate.get_observations().clone(),
+        warnings: WarningList::from_request(current_aggregate.get_warnings(), &req),
+    }))
+}
@@ -0,0 +1,87 @@
+use actix_web::{HttpRequest, HttpResponse, Error};
+use actix_web::web::Data;
+use chrono::NaiveDate;
+use crate::web::AppState;
+use crate::web::metrics::CacheStatus;
+use tracing::field;
+use crate::web::render::{self, Format};
+use weather_core::date;
+
+use actix_web::error;
+use crate::web::action::{RangeUserResponse, LocationErrorResponses, LocationParams, WarningList, QueryError, create_aggregate_manager, query_params, location_from_request, location_error_response, location_timezone};
+
+/// No provider forecasts that far, longer ranges are most likely a client's mistake.
+const MAX_RANGE_DAYS: u32 = 16;
+
+/// Forecast for a period of up to 16 days, both ends included.
+#[utoipa::path(
+    get,
+    path = "/weather/{location}/range",
+    operation_id = "range",
+    tag = "weather",
+    params(
+        ("location" = String, Path, description = "City (`Moscow`, `Paris, FR`), coordinates (`55.75,37.61`), `zip:...` or `iata:...`"),
+        ("from" = String, Query, description = "First day, `2019-03-26`"),
+        ("to" = String, Query, description = "Last day, `2019-03-27`"),
+        ("format" = Option<String>, Query, description = "`json`, `csv`, `xml` or `text`, overrides `Accept`"),
+        LocationParams,
+    ),
+    responses(
+        (status = 200, description = "Forecast averaged over providers and the providers behind each day", content(
+            (RangeUserResponse = "application/json"),
+            (String = "text/csv"),
+            (String = "application/xml"),
+            (String = "text/plain"),
+        )),
+        LocationErrorResponses,
+    )
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "range", location = field::Empty, cache = CacheStatus::Bypass.as_str()))]
+pub async fn handle(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, Error> {
+    let location = match location_from_request(&req, &state) {
+        Ok(location) => location,
+        Err(e) => return Ok(location_error_response(e)),
+    };
+
+    let (from, to) = range_from_request(&req).map_err(error::ErrorBadRequest)?;
+
+    let today = date::today_in(location_timezone(&state, &location));
+
+    let format = Format::from_request(&req).map_err(error::ErrorBadRequest)?;
+
+    let forecast_aggregate = create_aggregate_manager(&state)
+        .get_forecast_aggregate_in_range(&location, from, to, today)
+        .await;
+
+    state.metrics.observe_aggregate("forecast", forecast_aggregate.provider_count());
+
+    let aggregate_result = forecast_aggregate.calculate_average_forecast();
+
+    Ok(render::forecast(format, &RangeUserResponse {
+        ok: aggregate_result.is_some(),
+        from: date::format_date(&from),
+        to: date::format_date(&to),
+        forecast: aggregate_result,
+        coverage: forecast_aggregate.calculate_coverage(),
+        warnings: WarningList::from_request(forecast_aggregate.get_warnings(), &req),
+    }, &location.to_string(), &forecast_aggregate))
+}
+
+pub fn range_from_request(req: &HttpRequest) -> Result<(NaiveDate, NaiveDate), QueryError> {
+    let query = query_params(req);
+
+    let param = |name: &str| -> Result<NaiveDate, QueryError> {
+        let value = query.get(name).map(|v| v.as_str()).unwrap_or("");
+
+        date::parse_date(value).ok_or(QueryError::InvalidParam { name: name.to_owned(), value: value.to_owned() })
+    };
+
+    check_range(param("from")?, param("to")?)
+}
+
+pub fn check_range(from: NaiveDate, to: NaiveDate) -> Result<(NaiveDate, NaiveDate), QueryError> {
+    match date::days_between(&from, &to) {
+        days if (1..=MAX_RANGE_DAYS).contains(&days) => Ok((from, to)),
+        _ => Err(QueryError::InvalidParam { name: String::from("to"), value: date::format_date(&to) }),
+    }
+}
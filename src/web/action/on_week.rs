@@ -1,42 +1,100 @@
-use actix_web::HttpRequest;
-use actix_web::FutureResponse;
-use actix_web::HttpResponse;
-use futures::Future;
-use crate::app::provider::apixu;
-use crate::app::provider::yahoo;
-use crate::app::WeatherAggregateManager;
+use actix_web::{HttpRequest, HttpResponse, Error};
+use actix_web::web::Data;
 
 use actix_web::error;
 use crate::web::AppState;
-use crate::web::action::ForecastUserResponse;
+use crate::web::metrics::CacheStatus;
+use tracing::field;
+use crate::web::render::{self, Format};
+use crate::web::action::{ForecastUserResponse, LocationErrorResponses, LocationParams, WarningList, QueryError, create_aggregate_manager, query_params, location_from_request, location_error_response, location_timezone};
+use weather_core::date;
+use weather_core::forecast::ForecastAggregate;
+use weather_core::location::Location;
 
-pub fn handle(req: &HttpRequest<AppState>) -> FutureResponse<HttpResponse> {
-    let location = req.match_info().get("location").unwrap();
+pub const DEFAULT_DAYS: u32 = 5;
+/// Apixu is asked for 7 days, longer windows would always come back without it.
+const MAX_DAYS: u32 = 7;
 
-    let config = req.state().config.lock().unwrap();
-
-    let aggregate = WeatherAggregateManager::new(vec![
-        Box::new(apixu::ApixuProvider::new(config.get("apixu_key").unwrap().clone())),
-        Box::new(yahoo::YahooProvider::new(
-            config.get("yahoo_app_id").unwrap().clone(),
-            config.get("yahoo_secret").unwrap().clone(),
-            config.get("yahoo_user_key").unwrap().clone(),
+/// Forecast for `days` days starting today in the location timezone.
+#[utoipa::path(
+    get,
+    path = "/weather/{location}/week",
+    operation_id = "week",
+    tag = "weather",
+    params(
+        ("location" = String, Path, description = "City (`Moscow`, `Paris, FR`), coordinates (`55.75,37.61`), `zip:...` or `iata:...`"),
+        ("days" = Option<u32>, Query, description = "Period length, from 1 to 7, 5 by default"),
+        ("format" = Option<String>, Query, description = "`json`, `csv`, `xml` or `text`, overrides `Accept`"),
+        LocationParams,
+    ),
+    responses(
+        (status = 200, description = "Forecast averaged over providers, `ok` is false when none answered", content(
+            (ForecastUserResponse = "application/json"),
+            (String = "text/csv"),
+            (String = "application/xml"),
+            (String = "text/plain"),
         )),
-    ]);
-
-    Box::new(
-        aggregate
-            .get_forecast_aggregate_on_week(location)
-            .and_then(|forecast_aggregate| {
-                let aggregate_result = forecast_aggregate.calculate_average_forecast();
-                Ok(HttpResponse::Ok().json(ForecastUserResponse {
-                    ok: aggregate_result.is_some(),
-                    forecast: aggregate_result,
-                    warnings: forecast_aggregate.get_warnings().clone()
-                }))
-            })
-            .map_err(|e| error::ErrorBadRequest(e))
+        LocationErrorResponses,
     )
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "week", location = field::Empty, cache = CacheStatus::Bypass.as_str()))]
+pub async fn handle(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, Error> {
+    let format = Format::from_request(&req).map_err(error::ErrorBadRequest)?;
+
+    let week = match week_aggregate(&req, &state).await {
+        Ok(week) => week,
+        Err(response) => return Ok(response),
+    };
+
+    let aggregate_result = week.aggregate.calculate_average_forecast();
+
+    Ok(render::forecast(format, &ForecastUserResponse {
+        ok: aggregate_result.is_some(),
+        date: None,
+        forecast: aggregate_result,
+        warnings: WarningList::from_request(week.aggregate.get_warnings(), &req),
+    }, &week.location.to_string(), &week.aggregate))
+}
 
+/// Location of a week request and the providers' forecasts for it.
+pub struct WeekAggregate {
+    pub location: Location,
+    pub aggregate: ForecastAggregate,
 }
 
+/// Resolves the location and `days` of the request and asks the providers for `days` days starting today
+/// in the location timezone, shared by `/week`, `/forecast.ics` and `/week.svg`.
+///
+/// Fails with the response to send: `location_error_response` or 400 for malformed `days`.
+pub async fn week_aggregate(req: &HttpRequest, state: &AppState) -> Result<WeekAggregate, HttpResponse> {
+    let location = location_from_request(req, state).map_err(location_error_response)?;
+
+    let days = days_from_request(req).map_err(|e| HttpResponse::from_error(error::ErrorBadRequest(e)))?;
+
+    let today = date::today_in(location_timezone(state, &location));
+
+    let aggregate = create_aggregate_manager(state)
+        .get_forecast_aggregate_on_week(&location, today, days)
+        .await;
+
+    state.metrics.observe_aggregate("forecast", aggregate.provider_count());
+
+    Ok(WeekAggregate { location, aggregate })
+}
+
+pub fn days_from_request(req: &HttpRequest) -> Result<u32, QueryError> {
+    match query_params(req).get("days") {
+        None => Ok(DEFAULT_DAYS),
+        Some(value) => value
+            .parse::<u32>()
+            .map_err(|_| QueryError::InvalidParam { name: String::from("days"), value: value.clone() })
+            .and_then(check_days),
+    }
+}
+
+pub fn check_days(days: u32) -> Result<u32, QueryError> {
+    match (1..=MAX_DAYS).contains(&days) {
+        true => Ok(days),
+        _ => Err(QueryError::InvalidParam { name: String::from("days"), value: days.to_string() }),
+    }
+}
@@ -0,0 +1,20 @@
+use actix_web::HttpResponse;
+use actix_web::web::Data;
+use crate::web::AppState;
+
+use crate::web::action::{ProvidersUserResponse, create_aggregate_manager};
+
+/// Capability table of the configured providers, queries outside of a provider's capabilities skip it.
+#[utoipa::path(
+    get,
+    path = "/providers",
+    operation_id = "providers",
+    tag = "weather",
+    responses((status = 200, description = "Configured providers", body = ProvidersUserResponse))
+)]
+pub async fn handle(state: Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(ProvidersUserResponse {
+        ok: true,
+        providers: create_aggregate_manager(&state).get_capabilities(),
+    })
+}
@@ -0,0 +1,271 @@
+//! `/v2` forecast routes: the v1 answer extended with location metadata, units, per-date statistics,
+//! provider contributions and cache metadata. Warnings are always structured.
+use std::collections::BTreeSet;
+use actix_web::{HttpRequest, HttpResponse, Error};
+use actix_web::error;
+use actix_web::web::{self, Data, ServiceConfig};
+use chrono::{NaiveDate, SecondsFormat, Utc};
+use utoipa::ToSchema;
+use weather_core::Date;
+use weather_core::date::{self, Period};
+use weather_core::forecast::{DateStatistics, ForecastAggregate};
+use weather_core::location::{Location, LocationType};
+use weather_core::warning::Warning;
+use crate::web::AppState;
+use crate::web::metrics::CacheStatus;
+use tracing::field;
+use crate::web::request_id::request_id;
+use crate::web::render::{self, Format};
+use crate::web::action::{LocationErrorResponses, LocationParams, create_aggregate_manager, location_from_request, location_error_response, location_timezone};
+use crate::web::action::{on_week, on_range};
+
+pub const TEMPERATURE_UNIT: &str = "celsius";
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ForecastV2Response {
+    pub ok: bool,
+    pub request_id: Option<String>,
+    pub location: LocationMetadata,
+    pub period: PeriodBounds,
+    pub units: Units,
+    /// One entry per date at least one provider forecasts.
+    pub days: Vec<DayForecast>,
+    /// Every provider asked, including the failed ones.
+    pub providers: Vec<ProviderContribution>,
+    pub warnings: Vec<Warning>,
+    pub cache: CacheMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LocationMetadata {
+    /// Location segment of the path as the client sent it.
+    pub query: String,
+    /// Resolved location (`Paris, FR` for a gazetteer place).
+    pub name: String,
+    #[serde(rename = "type")]
+    pub location_type: LocationType,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    /// IANA timezone relative dates are resolved in.
+    pub timezone: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PeriodBounds {
+    #[schema(value_type = String)]
+    pub from: Date,
+    #[schema(value_type = String)]
+    pub to: Date,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Units {
+    /// `celsius`.
+    pub temperature: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DayForecast {
+    #[schema(value_type = String)]
+    pub date: Date,
+    #[serde(flatten)]
+    pub statistics: DateStatistics,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProviderContribution {
+    pub name: String,
+    /// Whether the provider's forecast made it into the answer.
+    pub ok: bool,
+    /// Dates the provider contributed.
+    pub days: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CacheMetadata {
+    /// Whether the forecast was served from a cache, single requests always go to the providers.
+    pub hit: bool,
+    /// When the providers were asked, RFC 3339.
+    pub fetched_at: String,
+}
+
+/// `/v2` routes, to be mounted in the `/v2` scope.
+pub fn routes(cfg: &mut ServiceConfig) {
+    cfg.route("/weather/{location}/on/{date}", web::get().to(on_date))
+        .route("/weather/{location}/week", web::get().to(on_week))
+        .route("/weather/{location}/range", web::get().to(on_range));
+}
+
+/// Forecast for one day with per-provider statistics.
+#[utoipa::path(
+    get,
+    path = "/v2/weather/{location}/on/{date}",
+    operation_id = "v2_on_date",
+    tag = "weather v2",
+    params(
+        ("location" = String, Path, description = "City (`Moscow`, `Paris, FR`), coordinates (`55.75,37.61`), `zip:...` or `iata:...`"),
+        ("date" = String, Path, description = "`2019-03-26`, `today`, `tomorrow`, `+3d`, week day (`saturday`, `sat`)"),
+        ("format" = Option<String>, Query, description = "`json`, `csv`, `xml` or `text`, overrides `Accept`"),
+        LocationParams,
+    ),
+    responses(
+        (status = 200, description = "Forecast with statistics, `ok` is false when no provider answered", content(
+            (ForecastV2Response = "application/json"),
+            (String = "text/csv"),
+            (String = "application/xml"),
+            (String = "text/plain"),
+        )),
+        LocationErrorResponses,
+    )
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "v2_on_date", location = field::Empty, cache = CacheStatus::Bypass.as_str()))]
+pub async fn on_date(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, Error> {
+    forecast(req, state, |req, today| {
+        date::resolve_date(req.match_info().get("date").unwrap_or(""), today)
+            .map(Period::Date)
+            .map_err(error::ErrorBadRequest)
+    }).await
+}
+
+/// Forecast for `days` days starting today in the location timezone, with per-provider statistics.
+#[utoipa::path(
+    get,
+    path = "/v2/weather/{location}/week",
+    operation_id = "v2_week",
+    tag = "weather v2",
+    params(
+        ("location" = String, Path, description = "City (`Moscow`, `Paris, FR`), coordinates (`55.75,37.61`), `zip:...` or `iata:...`"),
+        ("days" = Option<u32>, Query, description = "Period length, from 1 to 7, 5 by default"),
+        ("format" = Option<String>, Query, description = "`json`, `csv`, `xml` or `text`, overrides `Accept`"),
+        LocationParams,
+    ),
+    responses(
+        (status = 200, description = "Forecast with statistics, `ok` is false when no provider answered", content(
+            (ForecastV2Response = "application/json"),
+            (String = "text/csv"),
+            (String = "application/xml"),
+            (String = "text/plain"),
+        )),
+        LocationErrorResponses,
+    )
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "v2_week", location = field::Empty, cache = CacheStatus::Bypass.as_str()))]
+pub async fn on_week(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, Error> {
+    forecast(req, state, |req, today| {
+        on_week::days_from_request(req)
+            .map(|days| Period::Week { today: *today, days })
+            .map_err(error::ErrorBadRequest)
+    }).await
+}
+
+/// Forecast for a period of up to 16 days with per-provider statistics.
+#[utoipa::path(
+    get,
+    path = "/v2/weather/{location}/range",
+    operation_id = "v2_range",
+    tag = "weather v2",
+    params(
+        ("location" = String, Path, description = "City (`Moscow`, `Paris, FR`), coordinates (`55.75,37.61`), `zip:...` or `iata:...`"),
+        ("from" = String, Query, description = "First day, `2019-03-26`"),
+        ("to" = String, Query, description = "Last day, `2019-03-27`"),
+        ("format" = Option<String>, Query, description = "`json`, `csv`, `xml` or `text`, overrides `Accept`"),
+        LocationParams,
+    ),
+    responses(
+        (status = 200, description = "Forecast with statistics, `ok` is false when no provider answered", content(
+            (ForecastV2Response = "application/json"),
+            (String = "text/csv"),
+            (String = "application/xml"),
+            (String = "text/plain"),
+        )),
+        LocationErrorResponses,
+    )
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "v2_range", location = field::Empty, cache = CacheStatus::Bypass.as_str()))]
+pub async fn on_range(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, Error> {
+    forecast(req, state, |req, _| {
+        on_range::range_from_request(req)
+            .map(|(from, to)| Period::Range { from, to })
+            .map_err(error::ErrorBadRequest)
+    }).await
+}
+
+/// Resolves the location, asks the providers for the period `period` builds and renders the v2 envelope.
+async fn forecast<F>(req: HttpRequest, state: Data<AppState>, period: F) -> Result<HttpResponse, Error>
+    where F: FnOnce(&HttpRequest, &NaiveDate) -> Result<Period, Error>
+{
+    let location = match location_from_request(&req, &state) {
+        Ok(location) => location,
+        Err(e) => return Ok(location_error_response(e)),
+    };
+
+    let timezone = location_timezone(&state, &location);
+    let today = date::today_in(timezone);
+    let period = period(&req, &today)?;
+    let format = Format::from_request(&req).map_err(error::ErrorBadRequest)?;
+
+    let fetched_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let forecast_aggregate = create_aggregate_manager(&state)
+        .get_forecast_aggregate_in_period(&location, period, today)
+        .await;
+
+    state.metrics.observe_aggregate("forecast", forecast_aggregate.provider_count());
+
+    let (from, to) = period.bounds();
+    let request_id = request_id(&req);
+
+    let days: Vec<DayForecast> = forecast_aggregate
+        .calculate_statistics()
+        .into_iter()
+        .map(|(date, statistics)| DayForecast { date, statistics })
+        .collect();
+
+    let response = ForecastV2Response {
+        ok: !days.is_empty(),
+        location: location_metadata(&req, &location, timezone.name()),
+        period: PeriodBounds { from: date::format_date(&from), to: date::format_date(&to) },
+        units: Units { temperature: String::from(TEMPERATURE_UNIT) },
+        providers: contributions(&forecast_aggregate, &days),
+        warnings: forecast_aggregate.get_warnings().iter().map(|w| w.clone().with_request_id(request_id.clone())).collect(),
+        cache: CacheMetadata { hit: false, fetched_at },
+        request_id,
+        days,
+    };
+
+    Ok(render::forecast(format, &response, &location.to_string(), &forecast_aggregate))
+}
+
+fn location_metadata(req: &HttpRequest, location: &Location, timezone: &str) -> LocationMetadata {
+    let coordinates = location.coordinates();
+
+    LocationMetadata {
+        query: req.match_info().get("location").unwrap_or("").to_owned(),
+        name: location.to_string(),
+        location_type: location.location_type(),
+        lat: coordinates.map(|(lat, _)| lat),
+        lon: coordinates.map(|(_, lon)| lon),
+        timezone: timezone.to_owned(),
+    }
+}
+
+/// Providers with the dates they contributed, then the ones only known from their warnings.
+fn contributions(aggregate: &ForecastAggregate, days: &[DayForecast]) -> Vec<ProviderContribution> {
+    let contributed: BTreeSet<&String> = days.iter().flat_map(|day| day.statistics.providers.keys()).collect();
+
+    let mut contributions: Vec<ProviderContribution> = contributed
+        .into_iter()
+        .map(|name| ProviderContribution {
+            name: name.clone(),
+            ok: true,
+            days: days.iter().filter(|day| day.statistics.providers.contains_key(name)).count(),
+        })
+        .collect();
+
+    for name in aggregate.get_warnings().iter().filter_map(|warning| warning.provider.as_ref()) {
+        if !contributions.iter().any(|contribution| &contribution.name == name) {
+            contributions.push(ProviderContribution { name: name.clone(), ok: false, days: 0 });
+        }
+    }
+
+    contributions
+}
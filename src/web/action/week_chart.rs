@@ -0,0 +1,39 @@
+use actix_web::{HttpRequest, HttpResponse, Error};
+use actix_web::web::Data;
+use crate::web::{chart, AppState};
+use crate::web::metrics::CacheStatus;
+use tracing::field;
+use crate::web::action::{LocationErrorResponses, LocationParams};
+use crate::web::action::on_week;
+
+/// Forecast for `days` days starting today as an SVG chart of every provider's series, the mean and the min/max band.
+#[utoipa::path(
+    get,
+    path = "/weather/{location}/week.svg",
+    operation_id = "week_svg",
+    tag = "weather",
+    params(
+        ("location" = String, Path, description = "City (`Moscow`, `Paris, FR`), coordinates (`55.75,37.61`), `zip:...` or `iata:...`"),
+        ("days" = Option<u32>, Query, description = "Period length, from 1 to 7, 5 by default"),
+        LocationParams,
+    ),
+    responses(
+        (status = 200, description = "Chart, a placeholder text when no provider answered", content(
+            (String = "image/svg+xml"),
+        )),
+        LocationErrorResponses,
+    )
+)]
+#[tracing::instrument(name = "handler", skip_all, fields(handler = "week_svg", location = field::Empty, cache = CacheStatus::Bypass.as_str()))]
+pub async fn handle(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, Error> {
+    let week = match on_week::week_aggregate(&req, &state).await {
+        Ok(week) => week,
+        Err(response) => return Ok(response),
+    };
+
+    let title = format!("{}: forecast, °C", week.location);
+
+    Ok(HttpResponse::Ok()
+        .content_type("image/svg+xml")
+        .body(chart::chart(&title, &week.aggregate.calculate_statistics())))
+}
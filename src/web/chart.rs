@@ -0,0 +1,165 @@
+//! Forecast as an SVG line chart: a series per provider, the mean and the band between the coldest and the warmest provider.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use quick_xml::escape::escape;
+use weather_core::{Date, Temperature};
+use weather_core::forecast::DateStatistics;
+
+const WIDTH: f64 = 720.0;
+const HEIGHT: f64 = 360.0;
+const MARGIN_LEFT: f64 = 48.0;
+/// Room for the legend.
+const MARGIN_RIGHT: f64 = 136.0;
+const MARGIN_TOP: f64 = 48.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+/// Provider series colours, taken in turn.
+const PALETTE: [&str; 6] = ["#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b"];
+const MEAN_COLOR: &str = "#222222";
+const BAND_COLOR: &str = "#9ecae1";
+const GRID_COLOR: &str = "#dddddd";
+/// Degrees between horizontal grid lines, the smallest step giving at most `MAX_TICKS` intervals is used.
+const TICK_STEPS: [f64; 6] = [1.0, 2.0, 5.0, 10.0, 20.0, 50.0];
+const MAX_TICKS: f64 = 6.0;
+
+/// Chart of `statistics` (dates in order) under `title`, a placeholder text when there is nothing to draw.
+pub fn chart(title: &str, statistics: &BTreeMap<Date, DateStatistics>) -> String {
+    let mut svg = String::new();
+
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+        w = WIDTH, h = HEIGHT
+    );
+    let _ = writeln!(svg, r#"<rect width="{}" height="{}" fill="white"/>"#, WIDTH, HEIGHT);
+    let _ = writeln!(svg, r#"<text x="{}" y="28" font-size="16">{}</text>"#, MARGIN_LEFT, escape(title));
+
+    if statistics.is_empty() {
+        let _ = writeln!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="#666666">No forecast available</text>"##,
+            WIDTH / 2.0, HEIGHT / 2.0
+        );
+        svg.push_str("</svg>\n");
+        return svg;
+    }
+
+    let scale = Scale::new(statistics);
+    let days: Vec<(&Date, &DateStatistics)> = statistics.iter().collect();
+
+    for tick in scale.ticks() {
+        let y = scale.y(tick);
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="{}"/><text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#,
+            MARGIN_LEFT, WIDTH - MARGIN_RIGHT, GRID_COLOR, MARGIN_LEFT - 8.0, y + 4.0, tick + 0.0, y = y
+        );
+    }
+
+    for (index, (date, _)) in days.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+            scale.x(index), HEIGHT - MARGIN_BOTTOM + 20.0, escape(date.get(5..).unwrap_or(date))
+        );
+    }
+
+    let band: Vec<(usize, Temperature)> = days.iter().enumerate().map(|(index, (_, day))| (index, day.max))
+        .chain(days.iter().enumerate().rev().map(|(index, (_, day))| (index, day.min)))
+        .collect();
+    let _ = writeln!(svg, r#"<polygon class="band" points="{}" fill="{}" fill-opacity="0.5"/>"#, scale.points(&band), BAND_COLOR);
+
+    let providers: BTreeSet<&String> = days.iter().flat_map(|(_, day)| day.providers.keys()).collect();
+
+    for (provider, color) in providers.iter().zip(PALETTE.iter().cycle()) {
+        let series: Vec<(usize, Temperature)> = days
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (_, day))| day.providers.get(*provider).map(|value| (index, *value)))
+            .collect();
+
+        let _ = writeln!(svg, r#"<g class="provider" data-provider="{}">"#, escape(provider.as_str()));
+        let _ = writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#, scale.points(&series), color);
+        for (index, value) in &series {
+            let _ = writeln!(svg, r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{}"/>"#, scale.x(*index), scale.y(*value), color);
+        }
+        svg.push_str("</g>\n");
+    }
+
+    let mean: Vec<(usize, Temperature)> = days.iter().enumerate().map(|(index, (_, day))| (index, day.mean)).collect();
+    let _ = writeln!(
+        svg,
+        r#"<polyline class="mean" points="{}" fill="none" stroke="{}" stroke-width="2" stroke-dasharray="6 4"/>"#,
+        scale.points(&mean), MEAN_COLOR
+    );
+
+    let legend = providers
+        .iter()
+        .zip(PALETTE.iter().cycle())
+        .map(|(provider, color)| (provider.as_str(), *color, r#"stroke-width="2""#))
+        .chain(vec![
+            ("mean", MEAN_COLOR, r#"stroke-width="2" stroke-dasharray="6 4""#),
+            ("min - max", BAND_COLOR, r#"stroke-width="8""#),
+        ]);
+
+    for (row, (label, color, style)) in legend.enumerate() {
+        let x = WIDTH - MARGIN_RIGHT + 16.0;
+        let y = MARGIN_TOP + 8.0 + 20.0 * row as f64;
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="{}" {}/><text x="{:.1}" y="{:.1}">{}</text>"#,
+            x, x + 20.0, color, style, x + 28.0, y + 4.0, escape(label), y = y
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Maps date indexes and temperatures onto the plot area.
+struct Scale {
+    from: f64,
+    to: f64,
+    step: f64,
+    dates: usize,
+}
+
+impl Scale {
+    fn new(statistics: &BTreeMap<Date, DateStatistics>) -> Self {
+        let min = statistics.values().map(|day| day.min).fold(f64::INFINITY, f64::min);
+        let max = statistics.values().map(|day| day.max).fold(f64::NEG_INFINITY, f64::max);
+
+        let step = TICK_STEPS.iter().copied().find(|step| (max - min) / step <= MAX_TICKS).unwrap_or(100.0);
+        let from = (min / step).floor() * step;
+        let to = ((max / step).ceil() * step).max(from + step);
+
+        Scale { from, to, step, dates: statistics.len() }
+    }
+
+    fn ticks(&self) -> Vec<f64> {
+        let count = ((self.to - self.from) / self.step).round() as usize;
+        (0..=count).map(|tick| self.from + self.step * tick as f64).collect()
+    }
+
+    /// A single date goes in the middle.
+    fn x(&self, index: usize) -> f64 {
+        let width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+
+        match self.dates {
+            0 | 1 => MARGIN_LEFT + width / 2.0,
+            dates => MARGIN_LEFT + width * index as f64 / (dates - 1) as f64,
+        }
+    }
+
+    fn y(&self, value: Temperature) -> f64 {
+        let height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+        MARGIN_TOP + height * (self.to - value) / (self.to - self.from)
+    }
+
+    fn points(&self, values: &[(usize, Temperature)]) -> String {
+        values
+            .iter()
+            .map(|(index, value)| format!("{:.1},{:.1}", self.x(*index), self.y(*value)))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
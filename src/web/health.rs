@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+use weather_core::provider::ProviderError;
+
+/// Recent call outcome of one provider.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ProviderStatus {
+    pub name: String,
+    pub healthy: bool,
+    pub calls: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+/// Tracks provider call outcomes for the readiness check.
+///
+/// Only provider failures (timeouts, server errors, rejected credentials) count, a provider not knowing
+/// a location is healthy. Providers nobody has called yet are considered healthy.
+#[derive(Default)]
+pub struct ProviderHealth {
+    statuses: Mutex<HashMap<String, ProviderStatus>>,
+    last_probe: Mutex<Option<Instant>>,
+}
+
+impl ProviderHealth {
+    pub fn new() -> Self {
+        ProviderHealth::default()
+    }
+
+    pub fn record(&self, provider: &str, error: Option<&ProviderError>) {
+        let mut statuses = self.statuses.lock().unwrap();
+        let status = statuses.entry(provider.to_owned()).or_insert_with(|| ProviderStatus { name: provider.to_owned(), ..ProviderStatus::default() });
+
+        status.calls += 1;
+
+        match error {
+            Some(e) if e.is_provider_failure() => {
+                status.consecutive_failures += 1;
+                status.last_error = Some(e.to_string());
+            }
+            _ => status.consecutive_failures = 0,
+        }
+    }
+
+    /// Status of every provider in `providers`, unhealthy after `failure_threshold` failures in a row.
+    pub fn statuses(&self, providers: &[String], failure_threshold: u32) -> Vec<ProviderStatus> {
+        let statuses = self.statuses.lock().unwrap();
+
+        providers
+            .iter()
+            .map(|name| {
+                let mut status = statuses.get(name).cloned().unwrap_or_else(|| ProviderStatus { name: name.clone(), ..ProviderStatus::default() });
+                status.healthy = status.consecutive_failures < failure_threshold;
+                status
+            })
+            .collect()
+    }
+
+    /// Whether a synthetic probe is due, marks it as started when it is.
+    pub fn start_probe(&self, interval: Duration) -> bool {
+        let mut last_probe = self.last_probe.lock().unwrap();
+
+        match *last_probe {
+            Some(started) if started.elapsed() < interval => false,
+            _ => {
+                *last_probe = Some(Instant::now());
+                true
+            }
+        }
+    }
+}
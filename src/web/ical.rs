@@ -0,0 +1,105 @@
+//! iCalendar (RFC 5545) feed of a forecast, one all-day event per date.
+use chrono::{DateTime, Duration, Utc};
+use weather_core::date;
+use weather_core::location::Location;
+use crate::web::render::ForecastRow;
+
+const PRODUCT_ID: &str = "-//weather-service//forecast//EN";
+/// How often calendar clients are asked to re-fetch the feed.
+pub const REFRESH_INTERVAL: &str = "PT1H";
+/// Content lines longer than this (in octets, without CRLF) are folded.
+const MAX_LINE_LEN: usize = 75;
+
+/// Calendar of `rows` for `location`, `stamp` is the time the feed was built.
+///
+/// Event UIDs depend only on the location key and the date, so clients update an event on refresh instead of adding one more,
+/// and locations with the same name (places with equal `name, CC`, coordinates differing in sign) never share an event.
+pub fn calendar(location: &Location, rows: &[ForecastRow], stamp: DateTime<Utc>) -> String {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let uid_suffix = percent_encode(&location.key());
+
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        format!("PRODID:{}", PRODUCT_ID),
+        String::from("CALSCALE:GREGORIAN"),
+        String::from("METHOD:PUBLISH"),
+        format!("X-WR-CALNAME:{}", escape(&format!("Weather in {}", location))),
+        format!("REFRESH-INTERVAL;VALUE=DURATION:{}", REFRESH_INTERVAL),
+        format!("X-PUBLISHED-TTL:{}", REFRESH_INTERVAL),
+    ];
+
+    for row in rows {
+        let day = match date::parse_date(&row.date) {
+            Some(day) => day,
+            None => continue,
+        };
+
+        lines.push(String::from("BEGIN:VEVENT"));
+        lines.push(format!("UID:{}-{}@weather-service", row.date, uid_suffix));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")));
+        lines.push(format!("DTEND;VALUE=DATE:{}", (day + Duration::days(1)).format("%Y%m%d")));
+        lines.push(format!("SUMMARY:{}", escape(&format!("{}: {}°C", location, row.value.round() as i64))));
+        lines.push(format!("DESCRIPTION:{}", escape(&format!(
+            "Average of {} providers: {:.1}°C, spread {:.1}°C", row.providers, row.value, row.spread
+        ))));
+        lines.push(String::from("TRANSP:TRANSPARENT"));
+        lines.push(String::from("END:VEVENT"));
+    }
+
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Escapes TEXT values: backslash, `;`, `,` and line breaks.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Splits `line` into CRLF terminated lines of at most 75 octets, continuations start with a space.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut line_len = 0;
+
+    for c in line.chars() {
+        if line_len + c.len_utf8() > MAX_LINE_LEN {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+/// `place:524901` -> `place%3A524901`, only ASCII letters, digits and `-._~` are kept as is.
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
@@ -0,0 +1,39 @@
+use opentelemetry_sdk::trace::Tracer;
+use tracing::Subscriber;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::{self, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Used when `RUST_LOG` is not set.
+pub const DEFAULT_FILTER: &str = "weather_service=info,weather_core=info";
+
+/// JSON lines, one object per event with its fields at the top level, the current span (`span`) and
+/// all spans it is in (`spans`): a provider call line carries the request id of its `http_request` span.
+///
+/// With a `tracer` the same spans are exported through OpenTelemetry, see `telemetry`.
+pub fn subscriber<W>(filter: EnvFilter, writer: W, tracer: Option<Tracer>) -> impl Subscriber + Send + Sync
+    where W: for<'w> MakeWriter<'w> + Send + Sync + 'static
+{
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_writer(writer))
+        .with(tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
+}
+
+/// Installs the JSON subscriber to stdout, `log` records (weather_core, actix) go through it too.
+pub fn init(tracer: Option<Tracer>) {
+    init_with_default(DEFAULT_FILTER, tracer);
+}
+
+/// Same as `init`, with `default_filter` used when `RUST_LOG` is not set.
+pub fn init_with_default(default_filter: &str, tracer: Option<Tracer>) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+
+    subscriber(filter, std::io::stdout, tracer).init();
+}
@@ -0,0 +1,250 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use actix_web::{Error, HttpResponse};
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::web::Data;
+use async_trait::async_trait;
+use tracing::Instrument;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use weather_core::current::CurrentConditions;
+use weather_core::forecast::Forecast;
+use weather_core::location::Location;
+use weather_core::provider::{WeatherProvider, ProviderError};
+use weather_core::provider::capabilities::ProviderCapabilities;
+use crate::web::AppState;
+use crate::web::health::ProviderHealth;
+
+const LATENCY_BUCKETS: [f64; 12] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+const PROVIDER_COUNT_BUCKETS: [f64; 5] = [0.0, 1.0, 2.0, 3.0, 5.0];
+
+/// Service metrics in Prometheus format, one registry per application state.
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    provider_requests: IntCounterVec,
+    provider_duration: HistogramVec,
+    provider_errors: IntCounterVec,
+    cache_lookups: IntCounterVec,
+    aggregate_providers: HistogramVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some(String::from("weather")), None).unwrap();
+
+        let metrics = Metrics {
+            http_requests: IntCounterVec::new(
+                Opts::new("http_requests_total", "HTTP requests by route pattern, method and status"),
+                &["route", "method", "status"],
+            ).unwrap(),
+            http_duration: HistogramVec::new(
+                HistogramOpts::new("http_request_duration_seconds", "HTTP request latency").buckets(LATENCY_BUCKETS.to_vec()),
+                &["route", "method", "status"],
+            ).unwrap(),
+            provider_requests: IntCounterVec::new(
+                Opts::new("provider_requests_total", "Provider calls by operation (forecast, current)"),
+                &["provider", "operation"],
+            ).unwrap(),
+            provider_duration: HistogramVec::new(
+                HistogramOpts::new("provider_request_duration_seconds", "Provider call latency").buckets(LATENCY_BUCKETS.to_vec()),
+                &["provider", "operation"],
+            ).unwrap(),
+            provider_errors: IntCounterVec::new(
+                Opts::new("provider_errors_total", "Failed provider calls by ProviderError kind"),
+                &["provider", "operation", "kind"],
+            ).unwrap(),
+            cache_lookups: IntCounterVec::new(
+                Opts::new("cache_lookups_total", "Cache lookups by result (hit, miss)"),
+                &["cache", "result"],
+            ).unwrap(),
+            aggregate_providers: HistogramVec::new(
+                HistogramOpts::new("aggregate_providers", "Providers contributing to an aggregate").buckets(PROVIDER_COUNT_BUCKETS.to_vec()),
+                &["kind"],
+            ).unwrap(),
+            registry,
+        };
+
+        metrics.registry.register(Box::new(metrics.http_requests.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.http_duration.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.provider_requests.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.provider_duration.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.provider_errors.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.cache_lookups.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.aggregate_providers.clone())).unwrap();
+
+        metrics
+    }
+
+    pub fn observe_http_request(&self, route: &str, method: &str, status: u16, duration: Duration) {
+        let status = status.to_string();
+        let labels = [route, method, status.as_str()];
+
+        self.http_requests.with_label_values(&labels).inc();
+        self.http_duration.with_label_values(&labels).observe(duration.as_secs_f64());
+    }
+
+    pub fn observe_provider_call(&self, provider: &str, operation: &str, duration: Duration, error: Option<&ProviderError>) {
+        self.provider_requests.with_label_values(&[provider, operation]).inc();
+        self.provider_duration.with_label_values(&[provider, operation]).observe(duration.as_secs_f64());
+
+        if let Some(e) = error {
+            self.provider_errors.with_label_values(&[provider, operation, e.kind()]).inc();
+        }
+    }
+
+    pub fn observe_cache_lookups(&self, cache: &str, hits: usize, misses: usize) {
+        self.cache_lookups.with_label_values(&[cache, "hit"]).inc_by(hits as u64);
+        self.cache_lookups.with_label_values(&[cache, "miss"]).inc_by(misses as u64);
+    }
+
+    /// `kind` is `forecast` or `current`.
+    pub fn observe_aggregate(&self, kind: &str, providers: usize) {
+        self.aggregate_providers.with_label_values(&[kind]).observe(providers as f64);
+    }
+
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer).unwrap();
+
+        String::from_utf8(buffer).unwrap()
+    }
+}
+
+/// Counts and times every request by its route pattern (`/weather/{location}/week`), not by the raw path.
+pub async fn track_requests(req: ServiceRequest, next: Next<impl MessageBody + 'static>) -> Result<ServiceResponse<BoxBody>, Error> {
+    let started = Instant::now();
+    let state = req.app_data::<Data<AppState>>().cloned();
+    let method = req.method().to_string();
+
+    let response = next.call(req).await?;
+
+    if let Some(state) = state {
+        let route = response.request().match_pattern().unwrap_or_else(|| String::from("unmatched"));
+        state.metrics.observe_http_request(&route, &method, response.status().as_u16(), started.elapsed());
+    }
+
+    Ok(response.map_into_boxed_body())
+}
+
+/// Metrics in the Prometheus text format.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    operation_id = "metrics",
+    tag = "service",
+    responses((status = 200, description = "Prometheus text exposition format", body = String, content_type = "text/plain"))
+)]
+pub async fn handle(state: Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(TextEncoder::new().format_type())
+        .body(state.metrics.render())
+}
+
+/// Whether a forecast was served from a cache, recorded on spans as `cache`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CacheStatus {
+    /// Served from the aggregate fetched for an earlier query of the same batch.
+    Hit,
+    /// Fetched from the providers to be shared by the queries of a batch.
+    Miss,
+    /// Fetched from the providers without a cache, as single requests are.
+    Bypass,
+}
+
+impl CacheStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheStatus::Hit => "hit",
+            CacheStatus::Miss => "miss",
+            CacheStatus::Bypass => "bypass",
+        }
+    }
+}
+
+/// Provider reporting its calls to the metrics and the health tracker, the aggregate manager doesn't know about it.
+pub struct InstrumentedProvider {
+    provider: Box<dyn WeatherProvider>,
+    metrics: Arc<Metrics>,
+    health: Arc<ProviderHealth>,
+    cache: CacheStatus,
+}
+
+impl InstrumentedProvider {
+    /// `cache` tells why the provider is called: to fill a cache (`Miss`) or with no cache at all (`Bypass`).
+    pub fn new(provider: Box<dyn WeatherProvider>, metrics: Arc<Metrics>, health: Arc<ProviderHealth>, cache: CacheStatus) -> Self {
+        InstrumentedProvider { provider, metrics, health, cache }
+    }
+
+    /// Span of one provider call, a child of the `http_request` span and so tagged with its request id.
+    fn span(&self, operation: &str, location: &Location) -> tracing::Span {
+        tracing::info_span!(
+            "provider_call",
+            provider = %self.provider.capabilities().name,
+            operation,
+            location = %location,
+            cache = self.cache.as_str(),
+            outcome = tracing::field::Empty,
+            error.kind = tracing::field::Empty,
+            otel.kind = "client",
+            otel.status_code = tracing::field::Empty,
+        )
+    }
+
+    fn observe<T>(&self, operation: &str, started: Instant, result: &Result<T, ProviderError>) {
+        let name = self.provider.capabilities().name;
+        let elapsed = started.elapsed();
+        let outcome = result.as_ref().err().map_or("ok", |e| e.kind());
+
+        let span = tracing::Span::current();
+        span.record("outcome", outcome);
+        if let Err(e) = result {
+            span.record("error.kind", e.kind());
+            span.record("otel.status_code", "ERROR");
+        }
+
+        tracing::info!(latency_ms = elapsed.as_millis() as u64, outcome, "provider call finished");
+
+        self.metrics.observe_provider_call(&name, operation, elapsed, result.as_ref().err());
+        self.health.record(&name, result.as_ref().err());
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for InstrumentedProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.provider.capabilities()
+    }
+
+    async fn get_forecast(&self, location: &Location) -> Result<Forecast, ProviderError> {
+        let span = self.span("forecast", location);
+
+        async {
+            let started = Instant::now();
+            let result = self.provider.get_forecast(location).await;
+            self.observe("forecast", started, &result);
+
+            result
+        }.instrument(span).await
+    }
+
+    async fn get_current_conditions(&self, location: &Location) -> Result<CurrentConditions, ProviderError> {
+        let span = self.span("current", location);
+
+        async {
+            let started = Instant::now();
+            let result = self.provider.get_current_conditions(location).await;
+            self.observe("current", started, &result);
+
+            result
+        }.instrument(span).await
+    }
+}
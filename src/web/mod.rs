@@ -1,10 +1,27 @@
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::collections::HashMap;
+use weather_core::geocoder::Geocoder;
+use weather_core::transport::HttpTransport;
 
 pub mod action;
+pub mod metrics;
+pub mod health;
+pub mod request_id;
+pub mod logging;
+pub mod telemetry;
+pub mod openapi;
+pub mod render;
+pub mod ical;
+pub mod chart;
 mod test;
 
 pub struct AppState {
     pub config: Arc<Mutex<HashMap<String, String>>>,
-}
\ No newline at end of file
+    pub geocoder: Option<Arc<Geocoder>>,
+    /// Shared by providers of all requests.
+    pub transport: Arc<dyn HttpTransport>,
+    pub metrics: Arc<metrics::Metrics>,
+    /// Recent provider call outcomes, for `/readyz`.
+    pub health: Arc<health::ProviderHealth>,
+}
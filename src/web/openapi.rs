@@ -0,0 +1,213 @@
+use std::fmt::Write as _;
+use actix_web::HttpResponse;
+use quick_xml::escape::escape;
+use serde_json::Value;
+use utoipa::OpenApi;
+use weather_core::current::{AverageConditions, CurrentConditions};
+use weather_core::forecast::{DateStatistics, Forecast};
+use weather_core::geocoder::Candidate;
+use weather_core::location::LocationType;
+use weather_core::provider::capabilities::{ProviderCapabilities, RateLimit, Variable};
+use weather_core::warning::{Warning, WarningCode};
+use crate::web::action::{self, *};
+use crate::web::action::v2::{self, CacheMetadata, DayForecast, ForecastV2Response, LocationMetadata, PeriodBounds, ProviderContribution, Units};
+use crate::web::action::batch::{BatchQuery, BatchQueryResult, BatchRange, BatchUserResponse, BatchWeek};
+use crate::web::health::ProviderStatus;
+use crate::web::metrics;
+
+const HTTP_METHODS: [&str; 7] = ["get", "post", "put", "patch", "delete", "head", "options"];
+const DOCS_STYLE: &str = "body{font-family:sans-serif;max-width:960px;margin:0 auto;padding:0 16px 32px;color:#222}\
+table{border-collapse:collapse;width:100%;margin:8px 0}th,td{border:1px solid #ddd;padding:4px 8px;text-align:left;vertical-align:top}\
+th{background:#f4f4f4}code{background:#f4f4f4;padding:0 3px}section{border-top:1px solid #ddd;margin-top:24px}\
+.method{display:inline-block;min-width:56px;color:#fff;background:#2b6cb0;padding:2px 6px;font-size:14px;text-align:center}";
+
+/// OpenAPI 3 description of the service, built from the `#[utoipa::path]` attributes of the handlers.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Weather service",
+        description = "Weather forecast averaged over several providers. Every unversioned `/weather/...` route is also served as `/v1/weather/...`, unchanged: warnings are plain strings unless `?warnings=structured` is passed. `/v2` warnings are always structured.",
+    ),
+    paths(
+        action::on_date::handle,
+        action::on_week::handle,
+        action::on_range::handle,
+        action::on_now::handle,
+        action::calendar::handle,
+        action::week_chart::handle,
+        action::batch::handle,
+        action::providers::handle,
+        action::health::healthz,
+        action::health::readyz,
+        metrics::handle,
+        v2::on_date,
+        v2::on_week,
+        v2::on_range,
+    ),
+    components(schemas(
+        ForecastUserResponse, RangeUserResponse, CurrentUserResponse, ProvidersUserResponse,
+        HealthUserResponse, ReadinessUserResponse, ProviderStatus, LocationCandidatesResponse, Candidate,
+        WarningList, Warning, WarningCode, Forecast, AverageConditions, CurrentConditions,
+        ProviderCapabilities, RateLimit, Variable, LocationType,
+        BatchQuery, BatchRange, BatchWeek, BatchQueryResult, BatchUserResponse,
+        ForecastV2Response, LocationMetadata, PeriodBounds, Units, DayForecast, DateStatistics, ProviderContribution, CacheMetadata,
+    )),
+    tags(
+        (name = "weather", description = "Forecasts and current conditions"),
+        (name = "weather v2", description = "Forecasts with per-date statistics and provider contributions"),
+        (name = "service", description = "Health and metrics"),
+    )
+)]
+pub struct ApiDoc;
+
+pub async fn handle() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+/// The OpenAPI document as a plain HTML page, readable without scripts or anything loaded from elsewhere.
+pub async fn docs() -> HttpResponse {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap_or_default();
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(docs_page(&spec))
+}
+
+/// Operations with their params and responses, then the schemas they refer to.
+fn docs_page(spec: &Value) -> String {
+    let info = &spec["info"];
+    let mut page = String::new();
+
+    let _ = writeln!(page, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(page, "<title>{} API</title>\n<style>{}</style>\n</head>\n<body>", text(&info["title"]), DOCS_STYLE);
+    let _ = writeln!(page, "<h1>{} API <small>{}</small></h1>", text(&info["title"]), text(&info["version"]));
+    let _ = writeln!(page, "<p>{}</p>", text(&info["description"]));
+    let _ = writeln!(page, r#"<p>Machine-readable description: <a href="/openapi.json">/openapi.json</a>.</p>"#);
+
+    for (path, item) in entries(&spec["paths"]) {
+        for (method, operation) in entries(item).filter(|(method, _)| HTTP_METHODS.contains(&method.as_str())) {
+            operation_section(&mut page, path, method, operation);
+        }
+    }
+
+    let _ = writeln!(page, "<section>\n<h2>Schemas</h2>");
+    for (name, schema) in entries(&spec["components"]["schemas"]) {
+        schema_section(&mut page, name, schema);
+    }
+    page.push_str("</section>\n</body>\n</html>\n");
+
+    page
+}
+
+fn operation_section(page: &mut String, path: &str, method: &str, operation: &Value) {
+    let _ = writeln!(page, r#"<section id="{}">"#, text(&operation["operationId"]));
+    let _ = writeln!(page, r#"<h2><span class="method">{}</span> <code>{}</code></h2>"#, method.to_uppercase(), escape(path));
+    for key in ["summary", "description"] {
+        if operation[key].is_string() {
+            let _ = writeln!(page, "<p>{}</p>", text(&operation[key]));
+        }
+    }
+
+    if let Some(parameters) = operation["parameters"].as_array().filter(|parameters| !parameters.is_empty()) {
+        page.push_str("<h3>Parameters</h3>\n<table>\n<tr><th>Name</th><th>In</th><th>Type</th><th>Description</th></tr>\n");
+        for parameter in parameters {
+            let required = if parameter["required"].as_bool().unwrap_or(false) { " (required)" } else { "" };
+            let _ = writeln!(
+                page,
+                "<tr><td><code>{}</code>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                text(&parameter["name"]), required, text(&parameter["in"]), schema_type(&parameter["schema"]), text(&parameter["description"])
+            );
+        }
+        page.push_str("</table>\n");
+    }
+
+    let body = &operation["requestBody"];
+    if body.is_object() {
+        let _ = writeln!(page, "<h3>Request body</h3>\n<p>{}</p>\n<p>{}</p>", text(&body["description"]), content_types(&body["content"]));
+    }
+
+    page.push_str("<h3>Responses</h3>\n<table>\n<tr><th>Status</th><th>Description</th><th>Content</th></tr>\n");
+    for (status, response) in entries(&operation["responses"]) {
+        let _ = writeln!(page, "<tr><td>{}</td><td>{}</td><td>{}</td></tr>", escape(status.as_str()), text(&response["description"]), content_types(&response["content"]));
+    }
+    page.push_str("</table>\n</section>\n");
+}
+
+fn schema_section(page: &mut String, name: &str, schema: &Value) {
+    let _ = writeln!(page, r#"<h3 id="schema-{}">{}</h3>"#, escape(name), escape(name));
+    if schema["description"].is_string() {
+        let _ = writeln!(page, "<p>{}</p>", text(&schema["description"]));
+    }
+
+    let properties: Vec<(&String, &Value)> = entries(&schema["properties"]).collect();
+    if properties.is_empty() {
+        let _ = writeln!(page, "<p>{}</p>", schema_type(schema));
+        return;
+    }
+
+    let required = |property: &str| schema["required"].as_array().is_some_and(|required| required.iter().any(|name| name == property));
+
+    page.push_str("<table>\n<tr><th>Field</th><th>Type</th><th>Description</th></tr>\n");
+    for (property, property_schema) in properties {
+        let _ = writeln!(
+            page,
+            "<tr><td><code>{}</code>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(property.as_str()), if required(property) { " (required)" } else { "" }, schema_type(property_schema), text(&property_schema["description"])
+        );
+    }
+    page.push_str("</table>\n");
+}
+
+/// `application/json: ForecastUserResponse`, one media type per line.
+fn content_types(content: &Value) -> String {
+    entries(content)
+        .map(|(media_type, media)| format!("<code>{}</code>: {}", escape(media_type.as_str()), schema_type(&media["schema"])))
+        .collect::<Vec<String>>()
+        .join("<br>")
+}
+
+/// Short HTML description of a schema, references link to the schema sections.
+fn schema_type(schema: &Value) -> String {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = escape(reference.rsplit('/').next().unwrap_or(reference));
+        return format!(r##"<a href="#schema-{}">{}</a>"##, name, name);
+    }
+
+    if let Some(variants) = schema["oneOf"].as_array().or_else(|| schema["anyOf"].as_array()) {
+        return variants.iter().map(schema_type).collect::<Vec<String>>().join(" | ");
+    }
+
+    if let Some(values) = schema["enum"].as_array() {
+        return format!("one of {}", values.iter().map(|value| format!("<code>{}</code>", text(value))).collect::<Vec<String>>().join(", "));
+    }
+
+    if schema["items"].is_object() {
+        return format!("array of {}", schema_type(&schema["items"]));
+    }
+
+    if schema["additionalProperties"].is_object() {
+        return format!("map of {}", schema_type(&schema["additionalProperties"]));
+    }
+
+    match &schema["type"] {
+        Value::String(name) => escape(name.as_str()).into_owned(),
+        Value::Array(names) => names.iter().map(text).collect::<Vec<String>>().join(" | "),
+        _ => String::from("any"),
+    }
+}
+
+fn entries(value: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    value.as_object().into_iter().flatten()
+}
+
+/// Escaped string value, `code` spans of the doc comments become `<code>`.
+fn text(value: &Value) -> String {
+    escape(value.as_str().unwrap_or(""))
+        .split('`')
+        .enumerate()
+        .map(|(index, part)| match index % 2 {
+            1 => format!("<code>{}</code>", part),
+            _ => part.to_owned(),
+        })
+        .collect()
+}
@@ -0,0 +1,184 @@
+//! Forecast answers as JSON, CSV, XML or a plain text table, picked by `?format=` or the `Accept` header.
+use std::fmt::Write as _;
+use std::io;
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::http::header::{self, Accept, Header};
+use quick_xml::Writer;
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use serde::Serialize;
+use weather_core::Date;
+use weather_core::forecast::ForecastAggregate;
+use weather_core::warning::Warning;
+use crate::web::action::{QueryError, query_params};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    Csv,
+    Xml,
+    Text,
+}
+
+impl Format {
+    /// `?format=` (`json`, `csv`, `xml`, `text`) wins over `Accept`, JSON when neither names a known format.
+    pub fn from_request(req: &HttpRequest) -> Result<Self, QueryError> {
+        if let Some(format) = query_params(req).get("format") {
+            return Format::parse(format).ok_or_else(|| QueryError::InvalidParam { name: String::from("format"), value: format.clone() });
+        }
+
+        let accepted = Accept::parse(req).map(|accept| accept.ranked()).unwrap_or_default();
+
+        Ok(accepted
+            .iter()
+            .find_map(|mime| match (mime.type_().as_str(), mime.subtype().as_str()) {
+                (_, "json") => Some(Format::Json),
+                ("text", "csv") => Some(Format::Csv),
+                (_, "xml") => Some(Format::Xml),
+                ("text", "plain") => Some(Format::Text),
+                ("*", "*") => Some(Format::Json),
+                _ => None,
+            })
+            .unwrap_or(Format::Json))
+    }
+
+    fn parse(format: &str) -> Option<Self> {
+        match format {
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            "xml" => Some(Format::Xml),
+            "text" => Some(Format::Text),
+            _ => None,
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Csv => "text/csv; charset=utf-8",
+            Format::Xml => "application/xml; charset=utf-8",
+            Format::Text => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+/// One date of the averaged forecast, `spread` is the difference between the warmest and the coldest provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastRow {
+    pub date: Date,
+    pub value: f64,
+    pub providers: usize,
+    pub spread: f64,
+}
+
+pub fn forecast_rows(aggregate: &ForecastAggregate) -> Vec<ForecastRow> {
+    aggregate
+        .calculate_statistics()
+        .into_iter()
+        .map(|(date, statistics)| ForecastRow {
+            date,
+            value: statistics.mean,
+            providers: statistics.providers.len(),
+            spread: statistics.max - statistics.min,
+        })
+        .collect()
+}
+
+/// `json` as is for JSON, otherwise the forecast of `aggregate` for `location` (and its warnings, but in CSV).
+pub fn forecast<T: Serialize>(format: Format, json: &T, location: &str, aggregate: &ForecastAggregate) -> HttpResponse {
+    let rows = forecast_rows(aggregate);
+    let warnings = aggregate.get_warnings();
+
+    let body = match format {
+        Format::Json => return HttpResponse::Ok().insert_header((header::VARY, "Accept")).json(json),
+        Format::Csv => csv(&rows),
+        Format::Xml => xml(location, &rows, warnings),
+        Format::Text => text(location, &rows, warnings),
+    };
+
+    match body {
+        Ok(body) => HttpResponse::Ok()
+            .insert_header((header::VARY, "Accept"))
+            .content_type(format.content_type())
+            .body(body),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+fn csv(rows: &[ForecastRow]) -> io::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    writer.write_record(["date", "value", "providers", "spread"])?;
+    for row in rows {
+        writer.write_record([row.date.clone(), format_value(row.value), row.providers.to_string(), format_value(row.spread)])?;
+    }
+
+    String::from_utf8(writer.into_inner().map_err(|e| e.into_error())?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn xml(location: &str, rows: &[ForecastRow], warnings: &[Warning]) -> io::Result<String> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("forecast")
+        .with_attribute(("ok", (!rows.is_empty()).to_string().as_str()))
+        .with_attribute(("location", location))
+        .write_inner_content(|writer| {
+            for row in rows {
+                writer
+                    .create_element("day")
+                    .with_attribute(("date", row.date.as_str()))
+                    .with_attribute(("value", format_value(row.value).as_str()))
+                    .with_attribute(("providers", row.providers.to_string().as_str()))
+                    .with_attribute(("spread", format_value(row.spread).as_str()))
+                    .write_empty()?;
+            }
+
+            for warning in warnings {
+                let mut element = writer
+                    .create_element("warning")
+                    .with_attribute(("code", warning_code(warning).as_str()))
+                    .with_attribute(("retryable", warning.retryable.to_string().as_str()));
+                if let Some(ref provider) = warning.provider {
+                    element = element.with_attribute(("provider", provider.as_str()));
+                }
+                element.write_text_content(BytesText::new(&warning.message))?;
+            }
+
+            Ok(())
+        })?;
+
+    String::from_utf8(writer.into_inner()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn text(location: &str, rows: &[ForecastRow], warnings: &[Warning]) -> io::Result<String> {
+    let mut body = format!("Forecast for {}\n\n", location);
+
+    if rows.is_empty() {
+        body.push_str("No forecast available\n");
+    } else {
+        let _ = writeln!(body, "{:<10}  {:>7}  {:>9}  {:>6}", "date", "value", "providers", "spread");
+        for row in rows {
+            let _ = writeln!(body, "{:<10}  {:>7.2}  {:>9}  {:>6.2}", row.date, row.value, row.providers, row.spread);
+        }
+    }
+
+    if !warnings.is_empty() {
+        body.push_str("\nWarnings:\n");
+        for warning in warnings {
+            let _ = writeln!(body, "  - {}", warning.message);
+        }
+    }
+
+    Ok(body)
+}
+
+/// Rounded to hundredths: `1.75` rather than `1.7500000000000002`.
+fn format_value(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    rounded.to_string()
+}
+
+fn warning_code(warning: &Warning) -> String {
+    serde_json::to_value(warning.code).ok().and_then(|code| code.as_str().map(String::from)).unwrap_or_default()
+}
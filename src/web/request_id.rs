@@ -0,0 +1,94 @@
+use std::time::Instant;
+use actix_web::{Error, HttpMessage, HttpRequest};
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use tracing::{field, Instrument, Span};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+/// Longer ids sent by clients are replaced with generated ones.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Id of one HTTP request, taken from `X-Request-Id` or generated (UUID v4).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    fn from_header(value: Option<&HeaderValue>) -> Self {
+        value
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.chars().all(|c| c.is_ascii_graphic()))
+            .map(|id| RequestId(id.to_owned()))
+            .unwrap_or_else(|| RequestId(Uuid::new_v4().to_string()))
+    }
+}
+
+/// Request id of a request passed through `assign_request_id`.
+pub fn request_id(req: &HttpRequest) -> Option<String> {
+    req.extensions().get::<RequestId>().map(|id| id.0.clone())
+}
+
+/// `http_request` span of a request passed through `assign_request_id`.
+#[derive(Clone)]
+struct RequestSpan(Span);
+
+/// Adds the resolved location to the handler span and the `http_request` span, the final log line picks it up.
+pub fn record_location(req: &HttpRequest, location: &str) {
+    Span::current().record("location", location);
+
+    if let Some(RequestSpan(span)) = req.extensions().get::<RequestSpan>() {
+        span.record("location", location);
+    }
+}
+
+/// Gives every request an id, runs it in an `http_request` span carrying the id and logs its outcome.
+///
+/// The id is sent back in `X-Request-Id`, provider calls of the request are logged in child spans.
+pub async fn assign_request_id(req: ServiceRequest, next: Next<impl MessageBody + 'static>) -> Result<ServiceResponse<BoxBody>, Error> {
+    let started = Instant::now();
+    let request_id = RequestId::from_header(req.headers().get(REQUEST_ID_HEADER));
+
+    let span = tracing::info_span!(
+        "http_request",
+        request_id = %request_id.0,
+        method = %req.method(),
+        path = %req.path(),
+        location = field::Empty,
+        route = field::Empty,
+        status = field::Empty,
+        otel.kind = "server",
+    );
+
+    req.extensions_mut().insert(request_id.clone());
+    req.extensions_mut().insert(RequestSpan(span.clone()));
+
+    let mut response = next.call(req).instrument(span.clone()).await?;
+
+    let route = response.request().match_pattern().unwrap_or_else(|| String::from("unmatched"));
+    let status = response.status();
+
+    span.record("route", route.as_str());
+    span.record("status", status.as_u16());
+
+    let outcome = match status.as_u16() {
+        500..=599 => "server_error",
+        400..=499 => "client_error",
+        _ => "ok",
+    };
+
+    span.in_scope(|| tracing::info!(
+        route = %route,
+        status = status.as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        outcome,
+        "request finished"
+    ));
+
+    if let Ok(value) = HeaderValue::from_str(&request_id.0) {
+        response.headers_mut().insert(HeaderName::from_static("x-request-id"), value);
+    }
+
+    Ok(response.map_into_boxed_body())
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::{SdkTracerProvider, Tracer};
+use thiserror::Error;
+
+pub const DEFAULT_SERVICE_NAME: &str = "weather_service";
+
+#[derive(Debug, Error)]
+pub enum TelemetryError {
+    #[error("OTLP exporter for {} not created: {}", .endpoint, .reason)]
+    Exporter {
+        endpoint: String,
+        reason: String,
+    },
+}
+
+/// Exports spans to an OTLP/HTTP (JSON) collector, in batches from a background thread.
+///
+/// `endpoint` is the full traces url, `http://127.0.0.1:4318/v1/traces` for a local collector.
+pub fn tracer_provider(endpoint: &str, service_name: &str) -> Result<SdkTracerProvider, TelemetryError> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_protocol(Protocol::HttpJson)
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| TelemetryError::Exporter { endpoint: endpoint.to_owned(), reason: e.to_string() })?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name.to_owned()).build())
+        .build())
+}
+
+/// Tracer provider for `otlp_endpoint` (and `otlp_service_name`) of the config, none when export is not configured.
+pub fn tracer_provider_from_config(config: &HashMap<String, String>) -> Result<Option<SdkTracerProvider>, TelemetryError> {
+    match config.get("otlp_endpoint") {
+        Some(endpoint) => {
+            let service_name = config.get("otlp_service_name").map(|name| name.as_str()).unwrap_or(DEFAULT_SERVICE_NAME);
+            tracer_provider(endpoint, service_name).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn tracer(provider: &SdkTracerProvider) -> Tracer {
+    provider.tracer(DEFAULT_SERVICE_NAME)
+}
@@ -1,82 +1,523 @@
 #![cfg(test)]
 extern crate config;
 
-use actix_web::{http, test, HttpMessage};
+use actix_web::{test, http, App, Error, HttpResponse, HttpServer};
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::web::{self, Data};
+use actix_http::Request;
 use crate::web::action::*;
-use actix_web::App;
 use crate::web::AppState;
+use crate::web::metrics::{self, Metrics};
+use crate::web::health::ProviderHealth;
+use crate::web::request_id::{self, REQUEST_ID_HEADER};
+use crate::web::{ical, logging, openapi, render, telemetry};
+use actix_web::middleware::from_fn;
+use weather_core::geocoder::{Geocoder, Place};
+use weather_core::location::Location;
+use weather_core::transport::HttpTransport;
+use weather_core::transport::mock::{MockTransport, MockResponse};
 use config::*;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::collections::HashMap;
-use crate::web;
 use chrono::Utc;
-use time::Duration;
+use chrono::Duration;
 
-fn create_app() -> App<web::AppState> {
+const APIXU_ERROR_RESPONSE: &str = r#"{"error": {"code": 1006, "message": "No matching location found."}}"#;
+const YAHOO_ERROR_RESPONSE: &str = r#"{"location": {}, "current_observation": {}, "forecasts": []}"#;
+
+/// Apixu forecast for 7 days starting today (UTC, there is no geocoder in tests).
+fn apixu_forecast_response() -> String {
+    let today = Utc::now().date_naive();
+    let days: Vec<serde_json::Value> = (0..7)
+        .map(|offset| serde_json::json!({
+            "date": (today + Duration::days(offset)).format("%Y-%m-%d").to_string(),
+            "day": {"avgtemp_c": 1.0 + offset as f64, "avgtemp_f": 34.0 + offset as f64}
+        }))
+        .collect();
+
+    serde_json::json!({"location": {"name": "Moscow", "tz_id": "UTC"}, "forecast": {"forecastday": days}}).to_string()
+}
+
+fn apixu_current_response() -> String {
+    serde_json::json!({
+        "location": {"name": "Moscow"},
+        "current": {"last_updated_epoch": Utc::now().timestamp(), "temp_c": 5.0, "wind_kph": 11.2, "humidity": 70}
+    }).to_string()
+}
+
+/// Yahoo forecast for 10 days starting today, dates are UTC midnights.
+fn yahoo_forecast_response() -> String {
+    let today = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let days: Vec<serde_json::Value> = (0..10)
+        .map(|offset| serde_json::json!({
+            "day": "Mon",
+            "date": (today + Duration::days(offset)).timestamp(),
+            "low": offset,
+            "high": offset + 3,
+            "text": "Cloudy",
+            "code": 26
+        }))
+        .collect();
+
+    serde_json::json!({
+        "location": {"city": "Moscow", "timezone_id": "UTC"},
+        "current_observation": {
+            "condition": {"text": "Cloudy", "code": 26, "temperature": 4},
+            "pubDate": Utc::now().timestamp()
+        },
+        "forecasts": days
+    }).to_string()
+}
+
+/// Upstreams as the live tests used to see them: both providers know Moscow, nobody knows
+/// UnknownCityInUnknownCountry, only Yahoo knows ascx.
+fn create_transport() -> MockTransport {
+    MockTransport::new()
+        .on(&["forecast.json", "q=UnknownCityInUnknownCountry"], MockResponse::status(400, APIXU_ERROR_RESPONSE))
+        .on(&["forecast.json", "q=ascx"], MockResponse::status(400, APIXU_ERROR_RESPONSE))
+        .on(&["forecastrss", "location=UnknownCityInUnknownCountry"], MockResponse::json(YAHOO_ERROR_RESPONSE))
+        .on(&["forecast.json"], MockResponse::json(&apixu_forecast_response()))
+        .on(&["current.json"], MockResponse::json(&apixu_current_response()))
+        .on(&["forecastrss"], MockResponse::json(&yahoo_forecast_response()))
+}
+
+async fn create_app() -> impl Service<Request, Response = ServiceResponse, Error = Error> {
+    create_app_with_transport(Arc::new(create_transport())).await
+}
+
+async fn create_app_with_transport(transport: Arc<dyn HttpTransport>) -> impl Service<Request, Response = ServiceResponse, Error = Error> {
+    create_app_with(transport, None).await
+}
+
+/// Moscow and two Springfields, enough for ambiguous and misspelled names.
+fn create_geocoder() -> Geocoder {
+    Geocoder::from_reader(concat!(
+        "524901\tMoscow\tMoscow\tМосква\t55.75222\t37.61556\tP\tPPLC\tRU\t\t48\t\t\t\t10381222\t\t144\tEurope/Moscow\t2019-03-20\n",
+        "4409896\tSpringfield\tSpringfield\t\t37.21533\t-93.29824\tP\tPPLA2\tUS\t\tMO\t077\t\t\t166810\t396\t393\tAmerica/Chicago\t2017-05-23\n",
+        "4250542\tSpringfield\tSpringfield\t\t39.80172\t-89.64371\tP\tPPLA\tUS\t\tIL\t167\t\t\t116565\t182\t184\tAmerica/Chicago\t2017-05-23\n",
+    ).as_bytes()).unwrap()
+}
+
+async fn create_app_with(transport: Arc<dyn HttpTransport>, geocoder: Option<Arc<Geocoder>>) -> impl Service<Request, Response = ServiceResponse, Error = Error> {
     let mut settings = Config::default();
     settings.merge(File::with_name("cfg/config_test.json")).unwrap();
     let conf = Arc::new(Mutex::new(settings.try_into::<HashMap<String, String>>().unwrap()));
 
-    App::with_state(AppState { config: conf.clone() })
-        .resource("/test_week/{location}", |r| r.h(on_week::handle))
-        .resource("/test_date/{location}/{date}", |r| r.h(on_date::handle))
+    test::init_service(
+        App::new()
+            .app_data(Data::new(AppState { config: conf.clone(), geocoder, transport, metrics: Arc::new(Metrics::new()), health: Arc::new(ProviderHealth::new()) }))
+            .wrap(from_fn(metrics::track_requests))
+            .wrap(from_fn(request_id::assign_request_id))
+            .route("/test_week/{location}", web::get().to(on_week::handle))
+            .route("/test_date/{location}/{date}", web::get().to(on_date::handle))
+            .route("/test_range/{location}", web::get().to(on_range::handle))
+            .route("/test_batch", web::post().to(batch::handle))
+            .route("/test_now/{location}", web::get().to(on_now::handle))
+            .route("/test_providers", web::get().to(providers::handle))
+            .route("/test_metrics", web::get().to(metrics::handle))
+            .route("/test_healthz", web::get().to(health::healthz))
+            .route("/test_readyz", web::get().to(health::readyz))
+            .route("/test_openapi", web::get().to(openapi::handle))
+            .route("/test_docs", web::get().to(openapi::docs))
+            .service(web::scope("/v1").configure(routes))
+            .service(web::scope("/v2").configure(v2::routes))
+    ).await
 }
 
-#[test]
-fn test_on_week_action() {
-    let mut srv = test::TestServer::with_factory(create_app);
+async fn get(uri: &str) -> (http::StatusCode, String) {
+    let app = create_app().await;
+    let response = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
 
-    let request = srv.client(http::Method::GET, "/test_week/Moscow").finish().unwrap();
-    let response = srv.execute(request.send()).unwrap();
+    let status = response.status();
+    let body_bytes = test::read_body(response).await;
 
-    let body_bytes = srv.execute(response.body()).unwrap();
+    (status, String::from(::std::str::from_utf8(&body_bytes).unwrap()))
+}
 
-    assert!(response.status().is_success());
-    assert_body_ok(::std::str::from_utf8(&body_bytes).unwrap());
+#[actix_rt::test]
+async fn test_on_week_action() {
+    let (status, body) = get("/test_week/Moscow").await;
+
+    assert!(status.is_success());
+    assert_body_ok(&body);
 }
 
-#[test]
-fn test_on_date_action() {
+#[actix_rt::test]
+async fn test_on_date_action() {
     let day = (Utc::now() + Duration::days(1)).format("%Y-%m-%d").to_string();
 
-    let mut srv = test::TestServer::with_factory(create_app);
+    let (status, body) = get(&format!("/test_date/Moscow/{}", day)).await;
 
-    let request = srv.client(http::Method::GET, &format!("/test_date/Moscow/{}", day)).finish().unwrap();
-    let response = srv.execute(request.send()).unwrap();
+    assert!(status.is_success());
+    assert_body_ok(&body);
+}
 
-    let body_bytes = srv.execute(response.body()).unwrap();
+#[actix_rt::test]
+async fn test_on_range_action() {
+    let from = Utc::now().format("%Y-%m-%d").to_string();
+    let to = (Utc::now() + Duration::days(3)).format("%Y-%m-%d").to_string();
 
-    assert!(response.status().is_success());
-    assert_body_ok(::std::str::from_utf8(&body_bytes).unwrap());
+    let (status, body) = get(&format!("/test_range/Moscow?from={}&to={}", from, to)).await;
+
+    assert!(status.is_success());
+
+    let json = serde_json::from_str::<RangeUserResponse>(&body).unwrap();
+    assert!(json.ok);
+    assert!(!json.coverage.is_empty());
 }
 
-#[test]
-fn test_on_week_action_fail() {
-    let mut srv = test::TestServer::with_factory(create_app);
+#[actix_rt::test]
+async fn test_on_range_action_fail_on_invalid_range() {
+    let (status, _) = get("/test_range/Moscow?from=2019-03-10&to=2019-03-01").await;
+
+    assert_eq!(status, http::StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_on_range_action_past_one_horizon() {
+    let from = Utc::now().format("%Y-%m-%d").to_string();
+    let to = (Utc::now() + Duration::days(8)).format("%Y-%m-%d").to_string();
+
+    let (status, body) = get(&format!("/test_range/Moscow?from={}&to={}&warnings=structured", from, to)).await;
+
+    assert!(status.is_success());
+
+    let json = serde_json::from_str::<RangeUserResponse>(&body).unwrap();
+    assert!(json.ok);
+    assert_eq!(json.coverage.len(), 9);
+    assert_eq!(json.coverage.values().filter(|providers| providers.len() == 2).count(), 7);
+    assert_eq!(json.coverage[&to], vec![String::from("Yahoo")]);
+
+    let warnings = serde_json::from_str::<serde_json::Value>(&body).unwrap()["warnings"].clone();
+    assert_eq!(warnings.as_array().unwrap().len(), 1);
+    assert_eq!(warnings[0]["code"], "partial_coverage");
+    assert_eq!(warnings[0]["provider"], "Apixu");
+    assert!(warnings[0]["message"].as_str().unwrap().ends_with(&format!("no forecast on 2 of the dates from {} to {} !", warnings[0]["date"].as_str().unwrap(), to)));
+}
 
-    let request = srv.client(http::Method::GET, "/test_week/UnknownCityInUnknownCountry").finish().unwrap();
-    let response = srv.execute(request.send()).unwrap();
+#[actix_rt::test]
+async fn test_on_range_action_past_every_horizon() {
+    let from = (Utc::now() + Duration::days(11)).format("%Y-%m-%d").to_string();
+    let to = (Utc::now() + Duration::days(13)).format("%Y-%m-%d").to_string();
 
-    let body_bytes = srv.execute(response.body()).unwrap();
+    let (status, body) = get(&format!("/test_range/Moscow?from={}&to={}&warnings=structured", from, to)).await;
+
+    assert!(status.is_success());
+
+    let json = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+    assert_eq!(json["ok"], false);
+
+    let mut skipped: Vec<(String, String)> = json["warnings"].as_array().unwrap().iter()
+        .map(|warning| (warning["code"].as_str().unwrap().to_owned(), warning["provider"].as_str().unwrap().to_owned()))
+        .collect();
+    skipped.sort();
+    assert_eq!(skipped, vec![(String::from("beyond_horizon"), String::from("Apixu")), (String::from("beyond_horizon"), String::from("Yahoo"))]);
+}
+
+#[actix_rt::test]
+async fn test_on_week_action_fail_on_lat_without_lon() {
+    let (status, body) = get("/test_week/Moscow?lat=55.75").await;
+
+    assert_eq!(status, http::StatusCode::BAD_REQUEST);
+    assert!(body.contains("lon is missing"));
+}
+
+#[actix_rt::test]
+async fn test_gazetteer_location_errors() {
+    let app = create_app_with(Arc::new(create_transport()), Some(Arc::new(create_geocoder()))).await;
+
+    let request = test::TestRequest::get().uri("/test_week/Springfield").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), http::StatusCode::MULTIPLE_CHOICES);
+    let json: LocationCandidatesResponse = test::read_body_json(response).await;
+    assert!(!json.ok);
+    let mut ids: Vec<u64> = json.candidates.iter().map(|candidate| candidate.id).collect();
+    ids.sort();
+    assert_eq!(ids, vec![4250542, 4409896]);
+
+    let request = test::TestRequest::get().uri("/test_week/Mosow").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    let json: LocationCandidatesResponse = test::read_body_json(response).await;
+    assert_eq!(json.candidates.iter().map(|candidate| candidate.name.as_str()).collect::<Vec<&str>>(), vec!["Moscow"]);
+
+    let request = test::TestRequest::get().uri("/test_week/Springfield?id=abc").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    let json: LocationCandidatesResponse = test::read_body_json(response).await;
+    assert_eq!(json.error, "Invalid id param: abc !");
+
+    let request = test::TestRequest::get().uri("/test_week/Springfield?id=4409896").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_on_date_action_fail_on_date_overflow() {
+    let (status, _) = get("/test_date/Moscow/+4000000000d").await;
+    assert_eq!(status, http::StatusCode::BAD_REQUEST);
+
+    let (status, _) = get("/v2/weather/Moscow/on/+4000000000d").await;
+    assert_eq!(status, http::StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_on_date_action_resolve_relative_date() {
+    let (status, body) = get("/test_date/Moscow/tomorrow").await;
+
+    assert!(status.is_success());
+
+    let json = serde_json::from_str::<ForecastUserResponse>(&body).unwrap();
+    assert!(json.ok);
+    assert!(json.date.is_some());
+}
+
+#[actix_rt::test]
+async fn test_batch_action() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::post()
+        .uri("/test_batch")
+        .set_json(serde_json::json!([
+            {"location": "Moscow", "week": {}},
+            {"id": "moscow-tomorrow", "location": "Moscow", "date": "tomorrow"},
+            {"location": "Moscow", "date": "tomorrow", "week": {}}
+        ]))
+        .to_request();
+    let response = test::call_service(&app, request).await;
 
     assert!(response.status().is_success());
-    assert_body_err(::std::str::from_utf8(&body_bytes).unwrap());
+
+    let body_bytes = test::read_body(response).await;
+
+    let json = serde_json::from_slice::<batch::BatchUserResponse>(&body_bytes).unwrap();
+    assert_eq!(json.results.len(), 3);
+    assert!(json.results.get("Moscow/week").unwrap().ok);
+    assert!(json.results.get("moscow-tomorrow").unwrap().ok);
+    assert!(!json.results.get("Moscow/on/tomorrow").unwrap().ok);
+}
+
+#[actix_rt::test]
+async fn test_batch_action_fail_on_duplicate_keys() {
+    let transport = Arc::new(create_transport());
+    let app = create_app_with_transport(transport.clone()).await;
+
+    for queries in [
+        serde_json::json!([{"id": "a", "location": "Moscow", "week": {}}, {"id": "a", "location": "ascx", "date": "tomorrow"}]),
+        serde_json::json!([{"location": "Moscow", "date": "tomorrow"}, {"location": "Moscow", "date": "tomorrow"}]),
+    ] {
+        let request = test::TestRequest::post().uri("/test_batch").set_json(queries).to_request();
+        let response = test::call_service(&app, request).await;
+
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    assert!(transport.requests().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_on_range_action_in_the_past() {
+    let transport = Arc::new(create_transport());
+    let app = create_app_with_transport(transport.clone()).await;
+
+    let from = (Utc::now() - Duration::days(5)).format("%Y-%m-%d").to_string();
+    let to = (Utc::now() - Duration::days(3)).format("%Y-%m-%d").to_string();
+    let request = test::TestRequest::get().uri(&format!("/test_range/Moscow?from={}&to={}&warnings=structured", from, to)).to_request();
+    let json: serde_json::Value = test::call_and_read_body_json(&app, request).await;
+
+    assert_eq!(json["ok"], false);
+    let warnings = json["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings.iter().all(|warning| warning["code"] == "beyond_horizon" && warning["date"] == to.as_str()));
+    assert!(transport.requests().is_empty());
 }
 
-#[test]
-fn test_on_week_ok_but_with_warning() {
-    let mut srv = test::TestServer::with_factory(create_app);
+#[actix_rt::test]
+async fn test_on_now_action() {
+    let (status, body) = get("/test_now/Moscow").await;
+
+    assert!(status.is_success());
+
+    let json = serde_json::from_str::<CurrentUserResponse>(&body).unwrap();
+    assert!(json.ok);
+    assert!(!json.observations.is_empty());
+}
+
+#[actix_rt::test]
+async fn test_providers_action() {
+    let (status, body) = get("/test_providers").await;
+
+    assert!(status.is_success());
+
+    let json = serde_json::from_str::<ProvidersUserResponse>(&body).unwrap();
+    assert_eq!(json.providers.len(), 2);
+    assert!(json.providers.iter().all(|provider| provider.horizon_days >= on_week::DEFAULT_DAYS));
+}
 
+#[actix_rt::test]
+async fn test_on_week_action_fail() {
+    let (status, body) = get("/test_week/UnknownCityInUnknownCountry").await;
+
+    assert!(status.is_success());
+    assert_body_err(&body);
+}
+
+#[actix_rt::test]
+async fn test_on_week_ok_but_with_warning() {
     //yahoo know about ascx city, apixu don't
-    let request = srv.client(http::Method::GET, "/test_week/ascx").finish().unwrap();
-    let response = srv.execute(request.send()).unwrap();
+    let (status, body) = get("/test_week/ascx").await;
+
+    assert!(status.is_success());
+    assert_body_ok(&body);
+    assert_body_warning_count(&body, 1);
+}
+
+#[actix_rt::test]
+async fn test_on_week_retryable_warning_on_provider_timeout() {
+    let transport = MockTransport::new()
+        .on(&["forecast.json"], MockResponse::json(&apixu_forecast_response()).delayed(std::time::Duration::from_secs(60)))
+        .on(&["forecastrss"], MockResponse::json(&yahoo_forecast_response()));
+    let app = create_app_with_transport(Arc::new(transport)).await;
+
+    let response = test::call_service(&app, test::TestRequest::get().uri("/test_week/Moscow?warnings=structured").to_request()).await;
+    let body_bytes = test::read_body(response).await;
+
+    let json = serde_json::from_slice::<ForecastUserResponse>(&body_bytes).unwrap();
+    assert!(json.ok);
+    match json.warnings {
+        WarningList::Structured(warnings) => {
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].provider, Some(String::from("Apixu")));
+            assert!(warnings[0].retryable);
+        }
+        WarningList::Legacy(_) => panic!("Structured warnings expected"),
+    }
+}
+
+#[actix_rt::test]
+async fn test_metrics_action() {
+    let app = create_app().await;
 
-    let body_bytes = srv.execute(response.body()).unwrap();
+    test::call_service(&app, test::TestRequest::get().uri("/test_week/Moscow").to_request()).await;
+    test::call_service(&app, test::TestRequest::get().uri("/test_week/ascx").to_request()).await;
+    test::call_service(&app, test::TestRequest::get().uri("/test_range/Moscow?from=2019-03-10&to=2019-03-01").to_request()).await;
+
+    let response = test::call_service(&app, test::TestRequest::get().uri("/test_metrics").to_request()).await;
+    assert!(response.status().is_success());
+
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+
+    assert!(body.contains(r#"weather_http_requests_total{method="GET",route="/test_week/{location}",status="200"} 2"#));
+    assert!(body.contains(r#"weather_http_requests_total{method="GET",route="/test_range/{location}",status="400"} 1"#));
+    assert!(body.contains(r#"weather_provider_requests_total{operation="forecast",provider="Apixu"} 2"#));
+    assert!(body.contains(r#"weather_provider_errors_total{kind="request_error",operation="forecast",provider="Apixu"} 1"#));
+    assert!(body.contains(r#"weather_aggregate_providers_bucket{kind="forecast",le="1"} 1"#));
+    assert!(body.contains("weather_provider_request_duration_seconds_bucket"));
+}
+
+#[actix_rt::test]
+async fn test_metrics_count_batch_location_cache() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::post()
+        .uri("/test_batch")
+        .set_json(serde_json::json!([
+            {"location": "Moscow", "week": {}},
+            {"location": "Moscow", "date": "tomorrow"},
+            {"location": "ascx", "date": "tomorrow"}
+        ]))
+        .to_request();
+    test::call_service(&app, request).await;
+
+    let response = test::call_service(&app, test::TestRequest::get().uri("/test_metrics").to_request()).await;
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+
+    assert!(body.contains(r#"weather_cache_lookups_total{cache="batch_location",result="hit"} 1"#));
+    assert!(body.contains(r#"weather_cache_lookups_total{cache="batch_location",result="miss"} 2"#));
+}
+
+#[actix_rt::test]
+async fn test_healthz_action() {
+    let (status, body) = get("/test_healthz").await;
+
+    assert!(status.is_success());
+    assert!(serde_json::from_str::<HealthUserResponse>(&body).unwrap().ok);
+}
+
+/// Readiness once the background probe has been recorded for every provider.
+async fn readyz_after_probe<S>(app: &S) -> (http::StatusCode, ReadinessUserResponse)
+    where S: Service<Request, Response = ServiceResponse, Error = Error>
+{
+    for _ in 0..100 {
+        let response = test::call_service(app, test::TestRequest::get().uri("/test_readyz").to_request()).await;
+        let status = response.status();
+        let json: ReadinessUserResponse = serde_json::from_slice(&test::read_body(response).await).unwrap();
+
+        if json.providers.iter().all(|provider| provider.calls > 0) {
+            return (status, json);
+        }
+        actix_rt::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    panic!("Probe outcomes not recorded");
+}
+
+#[actix_rt::test]
+async fn test_readyz_action_probe_providers() {
+    let app = create_app().await;
+
+    let (status, json) = readyz_after_probe(&app).await;
+
+    assert!(status.is_success());
+    assert!(json.ok);
+    assert_eq!(json.healthy_providers, 2);
+    assert!(json.providers.iter().all(|provider| provider.calls == 1));
+}
+
+#[actix_rt::test]
+async fn test_readyz_action_not_waiting_for_probe() {
+    let transport = MockTransport::new()
+        .on(&["current.json"], MockResponse::json(&apixu_current_response()).delayed(std::time::Duration::from_secs(60)))
+        .on(&["forecastrss"], MockResponse::json(&yahoo_forecast_response()).delayed(std::time::Duration::from_secs(60)));
+    let app = create_app_with_transport(Arc::new(transport)).await;
+
+    let request = test::call_service(&app, test::TestRequest::get().uri("/test_readyz").to_request());
+    let response = actix_rt::time::timeout(std::time::Duration::from_secs(1), request).await.expect("readyz waited for the probe");
+
+    assert!(response.status().is_success());
+
+    let json: ReadinessUserResponse = serde_json::from_slice(&test::read_body(response).await).unwrap();
+    assert!(json.providers.iter().all(|provider| provider.calls == 0 && provider.healthy));
+}
+
+#[actix_rt::test]
+async fn test_readyz_action_fail_on_rejected_key() {
+    let transport = MockTransport::new()
+        .on(&["current.json"], MockResponse::status(403, r#"{"error": {"code": 2008, "message": "API key has been disabled."}}"#))
+        .on(&["forecastrss"], MockResponse::json(&yahoo_forecast_response()));
+    let app = create_app_with_transport(Arc::new(transport)).await;
+
+    let (status, json) = readyz_after_probe(&app).await;
+    assert_eq!(status, http::StatusCode::SERVICE_UNAVAILABLE);
+
+    assert!(!json.ok);
+    assert_eq!(json.healthy_providers, 1);
+
+    let apixu = json.providers.iter().find(|provider| provider.name == "Apixu").unwrap();
+    assert!(!apixu.healthy);
+    assert_eq!(apixu.consecutive_failures, 1);
+    assert!(apixu.last_error.is_some());
+}
+
+#[actix_rt::test]
+async fn test_readyz_action_ignore_unknown_locations() {
+    let app = create_app().await;
+
+    test::call_service(&app, test::TestRequest::get().uri("/test_week/UnknownCityInUnknownCountry").to_request()).await;
+    let response = test::call_service(&app, test::TestRequest::get().uri("/test_readyz").to_request()).await;
 
     assert!(response.status().is_success());
-    assert_body_ok(::std::str::from_utf8(&body_bytes).unwrap());
-    assert_body_warning_count(::std::str::from_utf8(&body_bytes).unwrap(), 1);
 }
 
 fn assert_body_ok(as_string: &str) {
@@ -88,11 +529,609 @@ fn assert_body_ok(as_string: &str) {
 fn assert_body_err(as_string: &str) {
     let json: ForecastUserResponse = serde_json::from_str::<ForecastUserResponse>(as_string).unwrap();
 
-    assert_eq!(false, json.ok);
+    assert!(!json.ok);
 }
 
 fn assert_body_warning_count(as_string: &str, count: i32) {
     let json: ForecastUserResponse = serde_json::from_str::<ForecastUserResponse>(as_string).unwrap();
 
     assert_eq!(count, json.warnings.len() as i32);
-}
\ No newline at end of file
+}
+#[actix_rt::test]
+async fn test_warnings_are_strings_unless_structured_requested() {
+    let (_, body) = get("/test_week/ascx").await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["warnings"].as_array().unwrap().len(), 1);
+    assert!(json["warnings"][0].as_str().unwrap().ends_with("provider: Apixu!"));
+
+    let (_, body) = get("/test_week/ascx?warnings=structured").await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["warnings"][0]["code"], "request_error");
+    assert_eq!(json["warnings"][0]["provider"], "Apixu");
+}
+
+#[actix_rt::test]
+async fn test_request_id_in_response_and_warnings() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::get().uri("/test_week/ascx?warnings=structured").insert_header((REQUEST_ID_HEADER, "req-42")).to_request();
+    let response = test::call_service(&app, request).await;
+
+    assert_eq!(response.headers().get(REQUEST_ID_HEADER).unwrap(), "req-42");
+
+    let json: ForecastUserResponse = test::read_body_json(response).await;
+    match json.warnings {
+        WarningList::Structured(warnings) => {
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].request_id, Some(String::from("req-42")));
+        }
+        WarningList::Legacy(_) => panic!("Structured warnings expected"),
+    }
+}
+
+#[actix_rt::test]
+async fn test_request_id_generated_when_missing_or_invalid() {
+    let app = create_app().await;
+
+    let plain = test::call_service(&app, test::TestRequest::get().uri("/test_healthz").to_request()).await;
+    let invalid = test::call_service(&app, test::TestRequest::get().uri("/test_healthz").insert_header((REQUEST_ID_HEADER, "has spaces")).to_request()).await;
+
+    for response in [plain, invalid] {
+        let id = response.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(id).is_ok(), "generated id expected, got {}", id);
+    }
+
+    let (_, body) = get("/test_week/ascx").await;
+    assert!(!body.contains("request_id"));
+}
+
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[actix_rt::test]
+async fn test_request_id_in_provider_call_logs() {
+    let buffer = LogBuffer::default();
+    let writer = buffer.clone();
+    let _guard = tracing::subscriber::set_default(logging::subscriber(logging::DEFAULT_FILTER.parse().unwrap(), move || writer.clone(), None));
+
+    let app = create_app().await;
+    let request = test::TestRequest::get().uri("/test_week/ascx?warnings=structured").insert_header((REQUEST_ID_HEADER, "req-42")).to_request();
+    test::call_service(&app, request).await;
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<serde_json::Value> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+    let provider_calls: Vec<&serde_json::Value> = lines.iter().filter(|line| line["message"] == "provider call finished").collect();
+    assert_eq!(provider_calls.len(), 2);
+
+    for line in &provider_calls {
+        assert_eq!(line["spans"][0]["request_id"], "req-42");
+        assert_eq!(line["span"]["location"], "ascx");
+        assert!(line["latency_ms"].is_u64());
+    }
+
+    let outcomes: Vec<(&str, &str)> = provider_calls.iter()
+        .map(|line| (line["span"]["provider"].as_str().unwrap(), line["outcome"].as_str().unwrap()))
+        .collect();
+    assert!(outcomes.contains(&("Yahoo", "ok")));
+    assert!(outcomes.contains(&("Apixu", "request_error")));
+
+    let finished = lines.iter().find(|line| line["message"] == "request finished").unwrap();
+    assert_eq!(finished["route"], "/test_week/{location}");
+    assert_eq!(finished["outcome"], "ok");
+    assert_eq!(finished["span"]["request_id"], "req-42");
+    assert_eq!(finished["span"]["location"], "ascx");
+    assert!(lines.iter().all(|line| line["level"] != "DEBUG"));
+}
+
+#[actix_rt::test]
+async fn test_provider_forecast_debug_logs() {
+    let buffer = LogBuffer::default();
+    let writer = buffer.clone();
+    let _guard = tracing::subscriber::set_default(logging::subscriber("weather_core=debug".parse().unwrap(), move || writer.clone(), None));
+
+    get("/test_week/Moscow?days=3").await;
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let mut received: Vec<(String, u64)> = output.lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|line| line["message"] == "forecast received")
+        .map(|line| {
+            assert_eq!(line["level"], "DEBUG");
+            assert_eq!(line["location"], "Moscow");
+            (line["provider"].as_str().unwrap().to_owned(), line["days"].as_u64().unwrap())
+        })
+        .collect();
+    received.sort();
+
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].0, "Apixu");
+    assert_eq!(received[1].0, "Yahoo");
+    assert!(received.iter().all(|(_, days)| *days >= 3));
+}
+
+/// OTLP/HTTP collector stand-in on a free port, keeps the JSON batches it receives.
+fn start_collector() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let received = batches.clone();
+
+    let server = HttpServer::new(move || {
+        let received = received.clone();
+
+        App::new().route("/v1/traces", web::post().to(move |body: web::Bytes| {
+            let received = received.clone();
+            async move {
+                received.lock().unwrap().push(serde_json::from_slice(&body).unwrap());
+                HttpResponse::Ok().finish()
+            }
+        }))
+    })
+        .workers(1)
+        .bind("127.0.0.1:0")
+        .unwrap();
+    let address = server.addrs()[0];
+
+    actix_web::rt::spawn(server.run());
+
+    (format!("http://{}/v1/traces", address), batches)
+}
+
+fn exported_spans(batches: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    batches.iter()
+        .flat_map(|batch| batch["resourceSpans"].as_array().unwrap().clone())
+        .flat_map(|resource| resource["scopeSpans"].as_array().unwrap().clone())
+        .flat_map(|scope| scope["spans"].as_array().unwrap().clone())
+        .collect()
+}
+
+fn span_attribute<'a>(span: &'a serde_json::Value, key: &str) -> Option<&'a serde_json::Value> {
+    span["attributes"].as_array().unwrap()
+        .iter()
+        .find(|attribute| attribute["key"] == key)
+        .map(|attribute| &attribute["value"])
+}
+
+#[actix_rt::test]
+async fn test_spans_exported_to_otlp_collector() {
+    let (endpoint, batches) = start_collector();
+    let provider = telemetry::tracer_provider(&endpoint, "weather_service_test").unwrap();
+    let _guard = tracing::subscriber::set_default(logging::subscriber(logging::DEFAULT_FILTER.parse().unwrap(), std::io::sink, Some(telemetry::tracer(&provider))));
+
+    let app = create_app().await;
+    let request = test::TestRequest::get().uri("/test_week/ascx?warnings=structured").insert_header((REQUEST_ID_HEADER, "req-42")).to_request();
+    test::call_service(&app, request).await;
+
+    // Flushing blocks until the collector answers, the collector server needs this thread to run.
+    let flushed = provider.clone();
+    actix_web::rt::task::spawn_blocking(move || flushed.force_flush()).await.unwrap().unwrap();
+
+    let batches = batches.lock().unwrap();
+    let spans = exported_spans(&batches);
+    let named = |name: &str| spans.iter().filter(|span| span["name"] == name).collect::<Vec<_>>();
+
+    let request_span = named("http_request")[0];
+    assert_eq!(span_attribute(request_span, "request_id").unwrap()["stringValue"], "req-42");
+    assert_eq!(span_attribute(request_span, "route").unwrap()["stringValue"], "/test_week/{location}");
+    assert_eq!(span_attribute(request_span, "location").unwrap()["stringValue"], "ascx");
+
+    let handler_span = named("handler")[0];
+    assert_eq!(handler_span["parentSpanId"], request_span["spanId"]);
+    assert_eq!(span_attribute(handler_span, "handler").unwrap()["stringValue"], "week");
+    assert_eq!(span_attribute(handler_span, "location").unwrap()["stringValue"], "ascx");
+    assert_eq!(span_attribute(handler_span, "cache").unwrap()["stringValue"], "bypass");
+
+    let aggregate_span = named("forecast_aggregate")[0];
+    assert_eq!(aggregate_span["parentSpanId"], handler_span["spanId"]);
+    assert_eq!(span_attribute(aggregate_span, "location").unwrap()["stringValue"], "ascx");
+    assert_eq!(span_attribute(aggregate_span, "providers").unwrap()["intValue"], "2");
+    assert_eq!(span_attribute(aggregate_span, "warnings").unwrap()["intValue"], "1");
+
+    let provider_spans = named("provider_call");
+    assert_eq!(provider_spans.len(), 2);
+
+    for span in &provider_spans {
+        assert_eq!(span["traceId"], request_span["traceId"]);
+        assert_eq!(span["parentSpanId"], aggregate_span["spanId"]);
+        assert_eq!(span_attribute(span, "location").unwrap()["stringValue"], "ascx");
+        assert_eq!(span_attribute(span, "cache").unwrap()["stringValue"], "bypass");
+    }
+
+    let outcomes: Vec<(&str, &str, Option<&str>)> = provider_spans.iter()
+        .map(|span| (
+            span_attribute(span, "provider").unwrap()["stringValue"].as_str().unwrap(),
+            span_attribute(span, "outcome").unwrap()["stringValue"].as_str().unwrap(),
+            span_attribute(span, "error.kind").map(|kind| kind["stringValue"].as_str().unwrap()),
+        ))
+        .collect();
+    assert!(outcomes.contains(&("Yahoo", "ok", None)));
+    assert!(outcomes.contains(&("Apixu", "request_error", Some("request_error"))));
+}
+
+#[actix_rt::test]
+async fn test_batch_cache_status_exported_to_otlp_collector() {
+    let (endpoint, batches) = start_collector();
+    let provider = telemetry::tracer_provider(&endpoint, "weather_service_test").unwrap();
+    let _guard = tracing::subscriber::set_default(logging::subscriber(logging::DEFAULT_FILTER.parse().unwrap(), std::io::sink, Some(telemetry::tracer(&provider))));
+
+    let app = create_app().await;
+    let queries = serde_json::json!([
+        {"id": "first", "location": "Moscow", "date": "tomorrow"},
+        {"id": "second", "location": "Moscow", "week": {"days": 3}},
+    ]);
+    test::call_service(&app, test::TestRequest::post().uri("/test_batch").set_json(queries).to_request()).await;
+
+    let flushed = provider.clone();
+    actix_web::rt::task::spawn_blocking(move || flushed.force_flush()).await.unwrap().unwrap();
+
+    let batches = batches.lock().unwrap();
+    let spans = exported_spans(&batches);
+    let named = |name: &str| spans.iter().filter(|span| span["name"] == name).collect::<Vec<_>>();
+
+    let mut queries: Vec<(&str, &str)> = named("batch_query").iter()
+        .map(|span| (
+            span_attribute(span, "key").unwrap()["stringValue"].as_str().unwrap(),
+            span_attribute(span, "cache").unwrap()["stringValue"].as_str().unwrap(),
+        ))
+        .collect();
+    queries.sort();
+    assert_eq!(queries, vec![("first", "miss"), ("second", "hit")]);
+
+    let location_span = named("batch_location")[0];
+    assert_eq!(span_attribute(location_span, "queries").unwrap()["intValue"], "2");
+
+    let provider_spans = named("provider_call");
+    assert_eq!(provider_spans.len(), 2);
+    assert!(provider_spans.iter().all(|span| span_attribute(span, "cache").unwrap()["stringValue"] == "miss"));
+}
+
+#[actix_rt::test]
+async fn test_openapi_action() {
+    let (status, body) = get("/test_openapi").await;
+    assert!(status.is_success());
+
+    let spec: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+    let paths: Vec<&String> = spec["paths"].as_object().unwrap().keys().collect();
+    for path in &["/weather/{location}/on/{date}", "/weather/{location}/week", "/weather/{location}/range", "/weather/{location}/now", "/weather/batch", "/providers", "/healthz", "/readyz", "/metrics", "/v2/weather/{location}/week"] {
+        assert!(paths.contains(&&path.to_string()), "{} is not documented", path);
+    }
+
+    let week = &spec["paths"]["/weather/{location}/week"]["get"];
+    let params: Vec<&str> = week["parameters"].as_array().unwrap().iter().map(|param| param["name"].as_str().unwrap()).collect();
+    assert_eq!(params, vec!["location", "days", "format", "lat", "lon", "id", "country", "warnings"]);
+    assert_eq!(week["responses"]["200"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/ForecastUserResponse");
+    assert!(week["responses"]["200"]["content"]["text/csv"].is_object());
+    assert_eq!(week["responses"]["404"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/LocationCandidatesResponse");
+
+    // Every endpoint taking a location documents the same location error answers.
+    for (path, operations) in spec["paths"].as_object().unwrap().iter().filter(|(path, _)| path.contains("{location}")) {
+        let responses = &operations["get"]["responses"];
+        for status in &["300", "400", "404"] {
+            assert_eq!(responses[*status]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/LocationCandidatesResponse", "{} {}", path, status);
+        }
+        assert_eq!(responses["400"]["content"]["text/plain"]["schema"]["type"], "string", "{}", path);
+    }
+
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["ForecastUserResponse"]["properties"]["warnings"]["$ref"], "#/components/schemas/WarningList");
+    assert_eq!(schemas["WarningCode"]["enum"].as_array().unwrap().len(), 8);
+
+    // Every referenced schema is described, type aliases (`Date`, `Temperature`) included.
+    for reference in body.split("\"#/components/schemas/").skip(1) {
+        let name = &reference[..reference.find('"').unwrap()];
+        assert!(schemas.get(name).is_some(), "schema {} is missing", name);
+    }
+
+    let mut operation_ids: Vec<&str> = spec["paths"].as_object().unwrap().values()
+        .flat_map(|path| path.as_object().unwrap().values())
+        .map(|operation| operation["operationId"].as_str().unwrap())
+        .collect();
+    operation_ids.sort();
+    operation_ids.dedup();
+    assert_eq!(operation_ids.len(), paths.len());
+}
+
+#[actix_rt::test]
+async fn test_docs_action() {
+    let (status, body) = get("/test_docs").await;
+
+    assert!(status.is_success());
+    assert!(body.contains("/openapi.json"));
+    assert!(!body.contains("<script"));
+    assert!(!body.contains("https://"));
+    assert!(body.contains(r#"<h2><span class="method">GET</span> <code>/weather/{location}/week</code></h2>"#));
+    assert!(body.contains(r##"<code>application/json</code>: <a href="#schema-ForecastUserResponse">ForecastUserResponse</a>"##));
+    assert!(body.contains(r#"<h3 id="schema-ForecastUserResponse">ForecastUserResponse</h3>"#));
+    assert!(body.contains("<code>beyond_horizon</code>"));
+}
+
+#[actix_rt::test]
+async fn test_v1_routes_answer_as_unversioned() {
+    let app = create_app().await;
+
+    for (unversioned, v1) in [("/test_week/Moscow", "/v1/weather/Moscow/week"), ("/test_date/Moscow/tomorrow", "/v1/weather/Moscow/on/tomorrow")] {
+        let expected: serde_json::Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri(unversioned).to_request()).await;
+        let actual: serde_json::Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri(v1).to_request()).await;
+
+        assert_eq!(actual, expected);
+    }
+
+    let response: ForecastUserResponse = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/v1/weather/ascx/week").to_request()).await;
+    assert!(matches!(response.warnings, WarningList::Legacy(ref warnings) if warnings.len() == 1));
+}
+
+#[actix_rt::test]
+async fn test_v1_on_date_keeps_string_warnings() {
+    let app = create_app().await;
+
+    let json: serde_json::Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/v1/weather/ascx/on/tomorrow").to_request()).await;
+
+    assert_eq!(json["ok"], true);
+    let warnings = json["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings.iter().all(|warning| warning.is_string()));
+}
+
+#[actix_rt::test]
+async fn test_v2_week_action() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::get().uri("/v2/weather/Moscow/week?days=3").insert_header((REQUEST_ID_HEADER, "req-42")).to_request();
+    let response: v2::ForecastV2Response = test::call_and_read_body_json(&app, request).await;
+
+    assert!(response.ok);
+    assert_eq!(response.request_id, Some(String::from("req-42")));
+    assert_eq!(response.location.query, "Moscow");
+    assert_eq!(response.location.timezone, "UTC");
+    assert_eq!(response.units.temperature, "celsius");
+    assert_eq!(response.period.from, Utc::now().format("%Y-%m-%d").to_string());
+    assert_eq!(response.days.len(), 3);
+    assert!(!response.cache.hit);
+
+    for day in &response.days {
+        assert_eq!(day.statistics.providers.len(), 2);
+        assert!(day.statistics.min <= day.statistics.mean && day.statistics.mean <= day.statistics.max);
+        assert_eq!(day.statistics.mean, (day.statistics.min + day.statistics.max) / 2.0);
+    }
+
+    let providers: Vec<(&str, bool, usize)> = response.providers.iter().map(|p| (p.name.as_str(), p.ok, p.days)).collect();
+    assert_eq!(providers, vec![("Apixu", true, 3), ("Yahoo", true, 3)]);
+}
+
+#[actix_rt::test]
+async fn test_v2_failed_provider_and_structured_warnings() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::get().uri("/v2/weather/ascx/on/tomorrow").insert_header((REQUEST_ID_HEADER, "req-42")).to_request();
+    let response: v2::ForecastV2Response = test::call_and_read_body_json(&app, request).await;
+
+    assert!(response.ok);
+    assert_eq!(response.period.from, response.period.to);
+    assert_eq!(response.days.len(), 1);
+
+    let providers: Vec<(&str, bool, usize)> = response.providers.iter().map(|p| (p.name.as_str(), p.ok, p.days)).collect();
+    assert_eq!(providers, vec![("Yahoo", true, 1), ("Apixu", false, 0)]);
+
+    assert_eq!(response.warnings.len(), 1);
+    assert_eq!(response.warnings[0].request_id, Some(String::from("req-42")));
+}
+
+#[actix_rt::test]
+async fn test_v2_unknown_location() {
+    let app = create_app().await;
+
+    let response: v2::ForecastV2Response = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/v2/weather/UnknownCityInUnknownCountry/week").to_request()).await;
+
+    assert!(!response.ok);
+    assert!(response.days.is_empty());
+    assert_eq!(response.warnings.len(), 2);
+    assert!(response.providers.iter().all(|provider| !provider.ok));
+}
+
+#[actix_rt::test]
+async fn test_week_action_as_csv() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::get().uri("/test_week/Moscow?days=3&format=csv").to_request();
+    let response = test::call_service(&app, request).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/csv; charset=utf-8");
+
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "date,value,providers,spread");
+    assert!(lines[1].starts_with(&Utc::now().format("%Y-%m-%d").to_string()));
+    assert!(lines[1..].iter().all(|line| line.split(',').nth(2) == Some("2")));
+}
+
+#[actix_rt::test]
+async fn test_date_action_as_xml_by_accept() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::get().uri("/test_date/ascx/tomorrow").insert_header(("Accept", "application/xml")).to_request();
+    let response = test::call_service(&app, request).await;
+
+    assert_eq!(response.headers().get("content-type").unwrap(), "application/xml; charset=utf-8");
+
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+    let tomorrow = (Utc::now() + Duration::days(1)).format("%Y-%m-%d").to_string();
+    assert!(body.starts_with("<?xml"));
+    assert!(body.contains(r#"<forecast ok="true" location="ascx">"#));
+    assert!(body.contains(&format!(r#"<day date="{}""#, tomorrow)));
+    assert!(body.contains(r#"providers="1" spread="0"/>"#));
+    assert!(body.contains(r#"<warning code="request_error" retryable="false" provider="Apixu">"#));
+}
+
+#[actix_rt::test]
+async fn test_range_action_as_text() {
+    let app = create_app().await;
+    let today = Utc::now().format("%Y-%m-%d").to_string();
+
+    let request = test::TestRequest::get().uri(&format!("/test_range/Moscow?from={}&to={}&format=text", today, today)).to_request();
+    let response = test::call_service(&app, request).await;
+
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/plain; charset=utf-8");
+
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+    assert!(body.starts_with("Forecast for Moscow\n"));
+    assert!(body.contains("date          value  providers  spread"));
+    assert!(body.lines().any(|line| line.starts_with(&today)));
+}
+
+#[actix_rt::test]
+async fn test_format_negotiation() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::get().uri("/test_week/Moscow?format=yaml").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+
+    let request = test::TestRequest::get().uri("/test_week/Moscow?format=json").insert_header(("Accept", "text/csv")).to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.headers().get("content-type").unwrap(), "application/json");
+    assert_eq!(response.headers().get("vary").unwrap(), "Accept");
+
+    let request = test::TestRequest::get().uri("/test_week/Moscow").insert_header(("Accept", "image/png, text/plain;q=0.5")).to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/plain; charset=utf-8");
+
+    let request = test::TestRequest::get().uri("/test_week/Moscow").insert_header(("Accept", "text/html, */*;q=0.1")).to_request();
+    let response: ForecastUserResponse = test::call_and_read_body_json(&app, request).await;
+    assert!(response.ok);
+
+    let request = test::TestRequest::get().uri("/v2/weather/Moscow/week?days=2").insert_header(("Accept", "text/csv")).to_request();
+    let body = test::call_and_read_body(&app, request).await;
+    assert_eq!(String::from_utf8(body.to_vec()).unwrap().lines().count(), 3);
+}
+
+#[actix_rt::test]
+async fn test_forecast_ics_action() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::get().uri("/v1/weather/Moscow/forecast.ics?days=3").to_request();
+    let response = test::call_service(&app, request).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/calendar; charset=utf-8");
+
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+    assert!(body.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(body.ends_with("END:VCALENDAR\r\n"));
+    assert!(body.contains("\r\nREFRESH-INTERVAL;VALUE=DURATION:PT1H\r\n"));
+    assert_eq!(body.matches("BEGIN:VEVENT").count(), 3);
+
+    let today = Utc::now().date_naive();
+    assert!(body.contains(&format!("\r\nUID:{}-Moscow@weather-service\r\n", today.format("%Y-%m-%d"))));
+    assert!(body.contains(&format!("\r\nDTSTART;VALUE=DATE:{}\r\nDTEND;VALUE=DATE:{}\r\n", today.format("%Y%m%d"), (today + Duration::days(1)).format("%Y%m%d"))));
+    assert!(body.lines().any(|line| line.starts_with("SUMMARY:Moscow: ") && line.ends_with("°C")));
+
+    let uids = |body: &str| body.lines().filter(|line| line.starts_with("UID:")).map(String::from).collect::<Vec<String>>();
+    let (_, again) = get("/v1/weather/Moscow/forecast.ics?days=3").await;
+    assert_eq!(uids(&body), uids(&again));
+
+    let (status, _) = get("/v1/weather/Moscow/forecast.ics?days=30").await;
+    assert_eq!(status, http::StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_ical_escaping_and_folding() {
+    let rows = vec![render::ForecastRow { date: String::from("2019-03-26"), value: -0.4, providers: 2, spread: 0.5 }];
+    let location = Location::City(format!("Springfield, US; {}", "Springfield ".repeat(8)));
+
+    let calendar = ical::calendar(&location, &rows, Utc::now());
+
+    assert!(calendar.split("\r\n").all(|line| line.len() <= 75));
+    let unfolded = calendar.replace("\r\n ", "");
+    assert!(unfolded.contains("\r\nSUMMARY:Springfield\\, US\\; Springfield"));
+    assert!(unfolded.contains(": 0°C\r\n"));
+    assert!(unfolded.contains("\r\nUID:2019-03-26-Springfield%2C%20US%3B%20Springfield%20"));
+}
+
+#[actix_rt::test]
+async fn test_ical_uids_of_same_named_locations_differ() {
+    let rows = vec![render::ForecastRow { date: String::from("2019-03-26"), value: 20.0, providers: 2, spread: 0.5 }];
+    let place = |id: u64| Location::Place(Place {
+        id,
+        name: String::from("Springfield"),
+        country_code: String::from("US"),
+        lat: 39.8,
+        lon: -89.6,
+        population: 100_000,
+        timezone: String::from("America/Chicago"),
+    });
+    let uid = |location: &Location| ical::calendar(location, &rows, Utc::now())
+        .lines()
+        .find(|line| line.starts_with("UID:"))
+        .map(String::from)
+        .unwrap();
+
+    assert_eq!(uid(&Location::Coordinates { lat: -33.9, lon: 18.4 }), "UID:2019-03-26--33.9%2C18.4@weather-service");
+    assert_eq!(uid(&Location::Coordinates { lat: 33.9, lon: 18.4 }), "UID:2019-03-26-33.9%2C18.4@weather-service");
+
+    assert_eq!(uid(&place(4250542)), "UID:2019-03-26-place%3A4250542@weather-service");
+    assert_ne!(uid(&place(4250542)), uid(&place(4951788)));
+}
+
+/// Fails on malformed XML, duplicated attributes included.
+fn assert_well_formed(xml: &str) {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(element)) | Ok(Event::Empty(element)) => {
+                if let Some(Err(e)) = element.attributes().find(|attribute| attribute.is_err()) {
+                    panic!("malformed attributes at {}: {}", reader.buffer_position(), e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => panic!("malformed XML at {}: {}", reader.buffer_position(), e),
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn test_week_svg_action() {
+    let app = create_app().await;
+
+    let request = test::TestRequest::get().uri("/v1/weather/Moscow/week.svg?days=3").to_request();
+    let response = test::call_service(&app, request).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "image/svg+xml");
+
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+    assert_well_formed(&body);
+    assert!(body.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(body.contains("Moscow: forecast, °C"));
+    assert!(body.contains(r#"<g class="provider" data-provider="Apixu">"#));
+    assert!(body.contains(r#"<g class="provider" data-provider="Yahoo">"#));
+    assert!(body.contains(r#"<polygon class="band""#));
+    assert!(body.contains(r#"<polyline class="mean""#));
+    assert_eq!(body.matches("<circle").count(), 6);
+    assert!(body.contains(&format!(">{}</text>", Utc::now().format("%m-%d"))));
+}
+
+#[actix_rt::test]
+async fn test_week_svg_action_without_forecast() {
+    let (status, body) = get("/v1/weather/UnknownCityInUnknownCountry/week.svg").await;
+
+    assert_eq!(status, http::StatusCode::OK);
+    assert_well_formed(&body);
+    assert!(body.contains("No forecast available"));
+    assert!(!body.contains("<polyline"));
+}
@@ -0,0 +1,173 @@
+use crate::Temperature;
+use crate::warning::Warning;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CurrentConditionsError {
+    #[error("Stale observation from provider {}, {} minutes old !", .provider_name, .age_minutes)]
+    StaleObservation {
+        provider_name: String,
+        age_minutes: i64,
+    },
+}
+
+/// Weather observed by a provider, `observed_at` is a unix timestamp.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CurrentConditions {
+    pub provider: String,
+    pub observed_at: i64,
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
+    pub temperature: Temperature,
+    pub humidity: Option<f64>,
+    pub wind_kph: Option<f64>,
+    pub condition: Option<String>,
+}
+
+/// Conditions averaged over providers, `observed_at` is the most recent observation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AverageConditions {
+    pub observed_at: i64,
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
+    pub temperature: Temperature,
+    pub humidity: Option<f64>,
+    pub wind_kph: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentConditionsAggregate {
+    observations: Vec<CurrentConditions>,
+    warnings: Vec<Warning>,
+}
+
+impl CurrentConditionsAggregate {
+    pub fn new(observations: Vec<CurrentConditions>, warnings: Vec<Warning>) -> Self {
+        CurrentConditionsAggregate {
+            observations,
+            warnings,
+        }
+    }
+
+    pub fn empty() -> Self {
+        CurrentConditionsAggregate::new(Vec::new(), Vec::new())
+    }
+
+    pub fn get_observations(&self) -> &Vec<CurrentConditions> {
+        &self.observations
+    }
+
+    pub fn get_warnings(&self) -> &Vec<Warning> {
+        &self.warnings
+    }
+
+    pub fn with_observation_result<E: Into<Warning>>(mut self, observation_opt: Result<CurrentConditions, E>) -> Self {
+        match observation_opt {
+            Ok(observation) => self.observations.push(observation),
+            Err(e) => self.warnings.push(e.into()),
+        };
+
+        self
+    }
+
+    /// Warns about observations older than `max_age_minutes`, they still take part in the average.
+    pub fn check_staleness(mut self, now: i64, max_age_minutes: i64) -> Self {
+        let stale: Vec<Warning> = self.observations
+            .iter()
+            .map(|observation| (observation, (now - observation.observed_at) / 60))
+            .filter(|(_, age_minutes)| *age_minutes > max_age_minutes)
+            .map(|(observation, age_minutes)| CurrentConditionsError::StaleObservation {
+                provider_name: observation.provider.clone(),
+                age_minutes,
+            }.into())
+            .collect();
+
+        self.warnings.extend(stale);
+        self
+    }
+
+    pub fn calculate_average_conditions(&self) -> Option<AverageConditions> {
+        let observed_at = self.observations.iter().map(|observation| observation.observed_at).max()?;
+
+        Some(AverageConditions {
+            observed_at,
+            temperature: average(self.observations.iter().map(|observation| observation.temperature))?,
+            humidity: average(self.observations.iter().filter_map(|observation| observation.humidity)),
+            wind_kph: average(self.observations.iter().filter_map(|observation| observation.wind_kph)),
+        })
+    }
+}
+
+fn average<I: Iterator<Item=f64>>(values: I) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+
+    match count {
+        0 => None,
+        _ => Some(sum / f64::from(count)),
+    }
+}
+
+#[cfg(test)]
+mod current_conditions_test {
+    use crate::current::{CurrentConditions, CurrentConditionsAggregate};
+    use crate::provider::ProviderError;
+    use crate::warning::WarningCode;
+
+    fn create_observation(provider: &str, observed_at: i64, temperature: f64, humidity: Option<f64>) -> CurrentConditions {
+        CurrentConditions {
+            provider: String::from(provider),
+            observed_at,
+            temperature,
+            humidity,
+            wind_kph: None,
+            condition: None,
+        }
+    }
+
+    #[test]
+    fn test_average_conditions() {
+        let aggregate = CurrentConditionsAggregate::new(vec![
+            create_observation("first", 1553600000, 4.0, Some(70.0)),
+            create_observation("second", 1553600600, 6.0, None),
+        ], vec![]);
+
+        let average = aggregate.calculate_average_conditions().unwrap();
+        assert_eq!(average.observed_at, 1553600600);
+        assert_eq!(average.temperature, 5.0);
+        assert_eq!(average.humidity, Some(70.0));
+        assert_eq!(average.wind_kph, None);
+    }
+
+    #[test]
+    fn test_average_conditions_return_none_if_no_observations() {
+        assert!(CurrentConditionsAggregate::empty().calculate_average_conditions().is_none());
+    }
+
+    #[test]
+    fn test_observation_errors_become_warnings() {
+        let aggregate = CurrentConditionsAggregate::empty()
+            .with_observation_result(Ok::<CurrentConditions, ProviderError>(create_observation("first", 1553600000, 4.0, None)))
+            .with_observation_result(Err(ProviderError::RequestError {
+                reason: String::from("Request time out"),
+                provider_name: String::from("second"),
+                retryable: true,
+            }));
+
+        assert_eq!(aggregate.get_observations().len(), 1);
+        assert_eq!(aggregate.get_warnings()[0].code, WarningCode::RequestError);
+    }
+
+    #[test]
+    fn test_stale_observations_warned() {
+        let aggregate = CurrentConditionsAggregate::new(vec![
+            create_observation("fresh", 1553600000, 4.0, None),
+            create_observation("stale", 1553600000 - 4 * 3600, 6.0, None),
+        ], vec![])
+            .check_staleness(1553600000 + 600, 180);
+
+        assert_eq!(aggregate.get_warnings().len(), 1);
+        assert_eq!(aggregate.get_warnings()[0].code, WarningCode::StaleObservation);
+        assert_eq!(aggregate.get_warnings()[0].provider, Some(String::from("stale")));
+        assert_eq!(aggregate.calculate_average_conditions().unwrap().temperature, 5.0);
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{Datelike, Duration, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
+use crate::Date;
+use thiserror::Error;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Error)]
+pub enum DateError {
+    #[error("Invalid date {} !", .date)]
+    InvalidDate {
+        date: String,
+    },
+}
+
+/// Current date at the location, providers date their forecasts in local time.
+pub fn today_in(tz: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&tz).naive_local().date()
+}
+
+pub fn format_date(date: &NaiveDate) -> Date {
+    date.format(DATE_FORMAT).to_string()
+}
+
+pub fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, DATE_FORMAT).ok()
+}
+
+/// Dates a forecast is asked for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Date(NaiveDate),
+    Range {
+        from: NaiveDate,
+        to: NaiveDate,
+    },
+    Week {
+        today: NaiveDate,
+        days: u32,
+    },
+}
+
+impl Period {
+    /// First and last date of the period.
+    pub fn bounds(&self) -> (NaiveDate, NaiveDate) {
+        match *self {
+            Period::Date(date) => (date, date),
+            Period::Range { from, to } => (from, to),
+            Period::Week { today, days } => (today, last_day(&today, days)),
+        }
+    }
+}
+
+/// Resolves `2019-03-26`, `today`, `tomorrow`, `+3d` or a weekday name (`saturday`, `sat`)
+/// relative to `today`. Weekday means the coming one, a week ahead when `today` is that weekday.
+pub fn resolve_date(spec: &str, today: &NaiveDate) -> Result<NaiveDate, DateError> {
+    let normalized = spec.trim().to_lowercase();
+    let invalid = || DateError::InvalidDate { date: spec.to_owned() };
+
+    if let Some(date) = parse_date(&normalized) {
+        return Ok(date);
+    }
+
+    match normalized.as_str() {
+        "today" => return Ok(*today),
+        "tomorrow" => return add_days(today, 1).ok_or_else(invalid),
+        _ => {}
+    };
+
+    if normalized.starts_with('+') && normalized.ends_with('d') && normalized.len() > 2 {
+        let offset = normalized[1..normalized.len() - 1].parse::<u32>().map_err(|_| invalid())?;
+        return add_days(today, offset).ok_or_else(invalid);
+    }
+
+    let weekday = parse_weekday(&normalized).ok_or_else(invalid)?;
+    let offset = match (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7 {
+        0 => 7,
+        offset => offset,
+    };
+
+    add_days(today, offset).ok_or_else(invalid)
+}
+
+/// `None` past the last date chrono can represent.
+fn add_days(date: &NaiveDate, days: u32) -> Option<NaiveDate> {
+    date.checked_add_signed(Duration::days(i64::from(days)))
+}
+
+fn parse_weekday(name: &str) -> Option<Weekday> {
+    let weekdays = [
+        ("monday", Weekday::Mon),
+        ("tuesday", Weekday::Tue),
+        ("wednesday", Weekday::Wed),
+        ("thursday", Weekday::Thu),
+        ("friday", Weekday::Fri),
+        ("saturday", Weekday::Sat),
+        ("sunday", Weekday::Sun),
+    ];
+
+    weekdays
+        .iter()
+        .find(|(full_name, _)| *full_name == name || (name.len() == 3 && full_name.starts_with(name)))
+        .map(|(_, weekday)| *weekday)
+}
+
+/// `days` consecutive dates starting at `from`.
+pub fn date_range(from: &NaiveDate, days: u32) -> Vec<Date> {
+    (0..days)
+        .map(|offset| format_date(&(*from + Duration::days(i64::from(offset)))))
+        .collect()
+}
+
+/// Number of dates from `from` to `to` inclusive, zero if `to` is before `from`.
+pub fn days_between(from: &NaiveDate, to: &NaiveDate) -> u32 {
+    let days = to.signed_duration_since(*from).num_days() + 1;
+
+    match days > 0 {
+        true => days as u32,
+        _ => 0,
+    }
+}
+
+/// Last date of the `days` long period starting at `from`.
+pub fn last_day(from: &NaiveDate, days: u32) -> NaiveDate {
+    *from + Duration::days(i64::from(days) - 1)
+}
+
+#[cfg(test)]
+mod date_test {
+    use crate::date::*;
+
+    #[test]
+    fn test_date_range() {
+        assert_eq!(
+            date_range(&NaiveDate::from_ymd_opt(2019, 2, 27).unwrap(), 3),
+            vec![String::from("2019-02-27"), String::from("2019-02-28"), String::from("2019-03-01")]
+        );
+        assert!(date_range(&NaiveDate::from_ymd_opt(2019, 2, 27).unwrap(), 0).is_empty());
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        let today = NaiveDate::from_ymd_opt(2019, 3, 26).unwrap();
+
+        assert_eq!(Period::Date(today).bounds(), (today, today));
+        assert_eq!(Period::Week { today, days: 5 }.bounds(), (today, NaiveDate::from_ymd_opt(2019, 3, 30).unwrap()));
+    }
+
+    #[test]
+    fn test_days_between() {
+        assert_eq!(days_between(&NaiveDate::from_ymd_opt(2019, 2, 27).unwrap(), &NaiveDate::from_ymd_opt(2019, 3, 1).unwrap()), 3);
+        assert_eq!(days_between(&NaiveDate::from_ymd_opt(2019, 3, 1).unwrap(), &NaiveDate::from_ymd_opt(2019, 3, 1).unwrap()), 1);
+        assert_eq!(days_between(&NaiveDate::from_ymd_opt(2019, 3, 2).unwrap(), &NaiveDate::from_ymd_opt(2019, 3, 1).unwrap()), 0);
+    }
+
+    #[test]
+    fn test_resolve_date_keywords() {
+        // 2019-03-26 is tuesday
+        let today = NaiveDate::from_ymd_opt(2019, 3, 26).unwrap();
+
+        assert_eq!(resolve_date("2019-04-01", &today).unwrap(), NaiveDate::from_ymd_opt(2019, 4, 1).unwrap());
+        assert_eq!(resolve_date("today", &today).unwrap(), today);
+        assert_eq!(resolve_date("Tomorrow", &today).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 27).unwrap());
+        assert_eq!(resolve_date("+3d", &today).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 29).unwrap());
+        assert_eq!(resolve_date("+0d", &today).unwrap(), today);
+    }
+
+    #[test]
+    fn test_resolve_date_weekday() {
+        let today = NaiveDate::from_ymd_opt(2019, 3, 26).unwrap();
+
+        assert_eq!(resolve_date("saturday", &today).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 30).unwrap());
+        assert_eq!(resolve_date("sat", &today).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 30).unwrap());
+        assert_eq!(resolve_date("tuesday", &today).unwrap(), NaiveDate::from_ymd_opt(2019, 4, 2).unwrap());
+        assert_eq!(resolve_date("MONDAY", &today).unwrap(), NaiveDate::from_ymd_opt(2019, 4, 1).unwrap());
+    }
+
+    #[test]
+    fn test_resolve_date_fail_on_garbage() {
+        let today = NaiveDate::from_ymd_opt(2019, 3, 26).unwrap();
+
+        assert!(resolve_date("yesterday", &today).is_err());
+        assert!(resolve_date("+d", &today).is_err());
+        assert!(resolve_date("+-1d", &today).is_err());
+        assert!(resolve_date("2019-02-30", &today).is_err());
+        assert!(resolve_date("+4000000000d", &today).is_err());
+        assert!(resolve_date("sat", &NaiveDate::MAX).is_err());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2019-03-26"), Some(NaiveDate::from_ymd_opt(2019, 3, 26).unwrap()));
+        assert_eq!(parse_date("2019-13-26"), None);
+        assert_eq!(parse_date("tomorrow"), None);
+    }
+}
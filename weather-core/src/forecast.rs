@@ -0,0 +1,649 @@
+use std::collections::BTreeMap;
+use crate::Date;
+use crate::Temperature;
+use crate::RawForecast;
+use crate::warning::Warning;
+use crate::date;
+use crate::date::Period;
+use chrono::NaiveDate;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ForecastError {
+    #[error("Unsupported date {} for provider {} !", .date, .provider_name)]
+    UnsupportedDate {
+        date: Date,
+        provider_name: String,
+    },
+
+    #[error("To short forecast for provider {} !", .provider_name)]
+    ToShortForecast {
+        provider_name: String,
+    },
+
+    #[error("Unsupported range {} - {} for provider {} !", .from, .to, .provider_name)]
+    UnsupportedRange {
+        from: Date,
+        to: Date,
+        provider_name: String,
+    },
+
+    #[error("Period from {} is beyond the forecast horizon of provider {}, its last day is {} !", .from, .provider_name, .last_day)]
+    BeyondHorizon {
+        from: Date,
+        last_day: Date,
+        provider_name: String,
+    },
+
+    #[error("Period to {} is in the past for provider {}, its forecast starts on {} !", .to, .provider_name, .first_day)]
+    PastPeriod {
+        to: Date,
+        first_day: Date,
+        provider_name: String,
+    },
+
+    #[error("Partial coverage for provider {}, no forecast on {} of the dates from {} to {} !", .provider_name, .missing, .date, .last_date)]
+    PartialCoverage {
+        /// First uncovered date.
+        date: Date,
+        last_date: Date,
+        /// Uncovered dates from `date` to `last_date`, fewer than the days between them when coverage has holes.
+        missing: usize,
+        provider_name: String,
+    },
+}
+
+/// Temperature (Celsius) by date: `{"2019-03-26": 1.75}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Forecast {
+    #[serde(flatten)]
+    #[cfg_attr(feature = "openapi", schema(value_type = BTreeMap<String, f64>))]
+    items: BTreeMap<Date, Temperature>,
+
+    #[serde(skip)]
+    source_name: String,
+}
+
+impl Forecast {
+    pub fn new(raw_forecast: RawForecast, source_name: String) -> Self {
+        Forecast { items: raw_forecast, source_name }
+    }
+
+    pub fn get_temperature_at(&self, date: Date) -> Option<&Temperature> {
+        self.items.get(&date)
+    }
+
+    pub fn into_date_forecast(self, date: Date) -> Result<Forecast, ForecastError> {
+        match self.items.get(&date) {
+            Some(temp) => Ok(Self::new(vec![(date, *temp)].into_iter().collect(), self.source_name)),
+            None => Err(ForecastError::UnsupportedDate {
+                date: date.clone(),
+                provider_name: self.source_name,
+            })
+        }
+    }
+
+    /// Number of forecast dates.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn get_source_name(&self) -> &str {
+        &self.source_name
+    }
+
+    /// Dates from `from` to `to` (inclusive) the provider has forecast for, fails only if there is none of them.
+    pub fn into_range_forecast(self, from: &NaiveDate, to: &NaiveDate) -> Result<Forecast, ForecastError> {
+        let (from, to) = (date::format_date(from), date::format_date(to));
+
+        let raw: RawForecast = match from <= to {
+            true => self.items
+                .range(from.clone()..=to.clone())
+                .map(|(date, temp)| (date.clone(), *temp))
+                .collect(),
+            _ => RawForecast::new(),
+        };
+
+        match raw.is_empty() {
+            false => Ok(Self::new(raw, self.source_name)),
+            _ => Err(ForecastError::UnsupportedRange { from, to, provider_name: self.source_name })
+        }
+    }
+
+    /// Exactly `days` dates starting at `today`, whatever the provider's series starts with.
+    pub fn into_week_forecast(self, today: &NaiveDate, days: u32) -> Result<Forecast, ForecastError> {
+        let provider_name = self.source_name.clone();
+        let last_day = date::last_day(today, days);
+
+        match self.into_range_forecast(today, &last_day) {
+            Ok(forecast) => match forecast.items.len() == days as usize {
+                true => Ok(forecast),
+                _ => Err(ForecastError::ToShortForecast { provider_name }),
+            },
+            Err(_) => Err(ForecastError::ToShortForecast { provider_name }),
+        }
+    }
+}
+
+/// Spread of the providers' forecasts on one date.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DateStatistics {
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
+    pub mean: Temperature,
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
+    pub min: Temperature,
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
+    pub max: Temperature,
+    /// Forecast of every provider having the date.
+    #[cfg_attr(feature = "openapi", schema(value_type = BTreeMap<String, f64>))]
+    pub providers: BTreeMap<String, Temperature>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastAggregate {
+    forecast_collection: Vec<Forecast>,
+    warnings: Vec<Warning>,
+}
+
+impl ForecastAggregate {
+    pub fn new(forecast_collection: Vec<Forecast>, warnings: Vec<Warning>) -> Self {
+        ForecastAggregate {
+            forecast_collection,
+            warnings,
+        }
+    }
+
+    pub fn empty() -> Self {
+        ForecastAggregate::new(Vec::new(), Vec::new())
+    }
+
+    pub fn get_warnings(&self) -> &Vec<Warning> {
+        &self.warnings
+    }
+
+    /// How many providers the aggregate is made of.
+    pub fn provider_count(&self) -> usize {
+        self.forecast_collection.len()
+    }
+
+    pub fn with_forecast_collection(self, forecast_c: Vec<Forecast>) -> Self {
+        ForecastAggregate::new(
+            vec![self.forecast_collection, forecast_c].into_iter().flat_map(|s| s.into_iter()).collect(),
+            self.warnings,
+        )
+    }
+
+    pub fn with_warning_collection(self, warning_c: Vec<Warning>) -> Self {
+        ForecastAggregate::new(
+            self.forecast_collection,
+            vec![self.warnings, warning_c].into_iter().flat_map(|s| s.into_iter()).collect(),
+        )
+    }
+
+    pub fn with_forecast_result<E: Into<Warning>>(self, forecast_opt: Result<Forecast, E>) -> Self {
+        match forecast_opt {
+            Ok(forecast) => self.with_forecast_collection(vec![forecast]),
+            Err(e) => self.with_warning_collection(vec![e.into()])
+        }
+    }
+
+    /// Forecasts and warnings of the named providers only, warnings not tied to a provider are kept.
+    pub fn retain_providers(self, provider_names: &[String]) -> Self {
+        ForecastAggregate::new(
+            self.forecast_collection.into_iter().filter(|forecast| provider_names.contains(&forecast.source_name)).collect(),
+            self.warnings.into_iter().filter(|warning| warning.provider.as_ref().is_none_or(|name| provider_names.contains(name))).collect(),
+        )
+    }
+
+    pub fn filter_by_date(self, date: Date) -> Self {
+        self.forecast_collection.into_iter().fold(ForecastAggregate::empty(), move |aggregate, forecast| {
+            aggregate.with_forecast_result(forecast.into_date_forecast(date.clone()))
+        })
+            .with_warning_collection(self.warnings)
+    }
+
+
+    pub fn into_week_aggregate(self, today: &NaiveDate, days: u32) -> Self {
+        self.forecast_collection.into_iter().fold(ForecastAggregate::empty(), |aggregate, forecast| {
+            aggregate.with_forecast_result(forecast.into_week_forecast(today, days))
+        })
+            .with_warning_collection(self.warnings)
+    }
+
+    /// Dates from `from` to `to` (inclusive), providers whose forecast covers the range partially
+    /// contribute the dates they have along with a warning.
+    pub fn filter_by_range(self, from: &NaiveDate, to: &NaiveDate) -> Self {
+        let dates = date::date_range(from, date::days_between(from, to));
+
+        self.forecast_collection.into_iter().fold(ForecastAggregate::empty(), |aggregate, forecast| {
+            match forecast.into_range_forecast(from, to) {
+                Ok(range_forecast) => {
+                    let missing: Vec<&Date> = dates.iter().filter(|date| !range_forecast.items.contains_key(*date)).collect();
+
+                    let partial_coverage: Vec<Warning> = match (missing.first(), missing.last()) {
+                        (Some(first), Some(last)) => vec![ForecastError::PartialCoverage {
+                            date: (*first).clone(),
+                            last_date: (*last).clone(),
+                            missing: missing.len(),
+                            provider_name: range_forecast.source_name.clone(),
+                        }.into()],
+                        _ => Vec::new(),
+                    };
+
+                    aggregate
+                        .with_forecast_collection(vec![range_forecast])
+                        .with_warning_collection(partial_coverage)
+                }
+                Err(e) => aggregate.with_forecast_result(Err(e)),
+            }
+        })
+            .with_warning_collection(self.warnings)
+    }
+
+    pub fn filter_by_period(self, period: &Period) -> Self {
+        match *period {
+            Period::Date(ref date) => self.filter_by_date(date::format_date(date)),
+            Period::Range { ref from, ref to } => self.filter_by_range(from, to),
+            Period::Week { ref today, days } => self.into_week_aggregate(today, days),
+        }
+    }
+
+    /// Providers contributing to each date.
+    pub fn calculate_coverage(&self) -> BTreeMap<Date, Vec<String>> {
+        let mut coverage: BTreeMap<Date, Vec<String>> = BTreeMap::new();
+
+        self.forecast_collection
+            .iter()
+            .for_each(|current_forecast| {
+                current_forecast.items.keys().for_each(|date| {
+                    coverage
+                        .entry(date.to_owned())
+                        .or_default()
+                        .push(current_forecast.source_name.clone());
+                });
+            });
+
+        coverage
+    }
+
+    /// Mean, min and max over the providers having a forecast on each date.
+    pub fn calculate_statistics(&self) -> BTreeMap<Date, DateStatistics> {
+        let mut statistics: BTreeMap<Date, DateStatistics> = BTreeMap::new();
+
+        self.forecast_collection
+            .iter()
+            .for_each(|current_forecast| {
+                current_forecast.items.iter().for_each(|(date, temp)| {
+                    statistics
+                        .entry(date.to_owned())
+                        .or_insert_with(|| DateStatistics { mean: 0.0, min: *temp, max: *temp, providers: BTreeMap::new() })
+                        .providers
+                        .insert(current_forecast.source_name.clone(), *temp);
+                });
+            });
+
+        statistics.values_mut().for_each(|date_statistics| {
+            let temps = date_statistics.providers.values();
+
+            date_statistics.mean = temps.clone().sum::<Temperature>() / date_statistics.providers.len() as f64;
+            date_statistics.min = temps.clone().cloned().fold(Temperature::INFINITY, Temperature::min);
+            date_statistics.max = temps.cloned().fold(Temperature::NEG_INFINITY, Temperature::max);
+        });
+
+        statistics
+    }
+
+    /// Average over the providers having a forecast on each date.
+    pub fn calculate_average_forecast(&self) -> Option<Forecast> {
+        if self.forecast_collection.is_empty() {
+            return None;
+        }
+
+        let mut totals: BTreeMap<Date, (Temperature, u32)> = BTreeMap::new();
+        self.forecast_collection
+            .iter()
+            .for_each(|current_forecast| {
+                current_forecast.items.iter().for_each(|(date, temp)| {
+                    let total = totals.entry(date.to_owned()).or_insert((0.0, 0));
+                    total.0 += temp;
+                    total.1 += 1;
+                });
+            });
+
+        Some(Forecast::new(
+            totals
+                .into_iter()
+                .map(|(date, (sum, count))| (date, sum / f64::from(count)))
+                .collect(),
+            String::from("multiple"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod forecast_aggregate_test {
+    use crate::forecast::Forecast;
+    use crate::RawForecast;
+    use crate::forecast::ForecastAggregate;
+    use crate::Date;
+    use crate::Temperature;
+    use crate::warning::WarningCode;
+    use crate::date::Period;
+    use chrono::NaiveDate;
+
+    fn create_forecast() -> Forecast {
+        Forecast::new(
+            vec![(String::from("2019-03-01"), 3.0), (String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0), (String::from("2019-03-04"), 6.0), (String::from("2019-03-05"), 7.0), (String::from("2019-03-06"), 8.0), (String::from("2019-03-07"), 9.0)].into_iter().collect::<RawForecast>(),
+            String::from("test"),
+        )
+    }
+
+    fn create_forecast_on_date(date: Date, temp: Temperature) -> Forecast {
+        Forecast::new(
+            vec![(date, temp)].into_iter().collect::<RawForecast>(),
+            String::from("test"),
+        )
+    }
+
+    #[test]
+    fn test_forecast_aggregate_filter_by_date_work_as_expected() {
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast(), self::create_forecast()], vec![])
+            .filter_by_date(String::from("2019-03-02"));
+
+        assert_eq!(aggregate.forecast_collection, vec![
+            self::create_forecast_on_date(String::from("2019-03-02"), 4.0),
+            self::create_forecast_on_date(String::from("2019-03-02"), 4.0)
+        ]);
+
+        assert_eq!(aggregate.warnings.len(), 0);
+    }
+
+    #[test]
+    fn test_forecast_aggregate_filter_by_date_add_warnings_for_invalid_forecasts() {
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast(), self::create_forecast_on_date(String::from("2019-03-05"), 4.0)], vec![])
+            .filter_by_date(String::from("2019-03-02"));
+
+        assert_eq!(aggregate.forecast_collection, vec![
+            self::create_forecast_on_date(String::from("2019-03-02"), 4.0),
+        ]);
+
+        assert_eq!(aggregate.warnings.len(), 1);
+        assert_eq!(aggregate.warnings[0].code, WarningCode::UnsupportedDate);
+        assert_eq!(aggregate.warnings[0].date, Some(String::from("2019-03-02")));
+    }
+
+    #[test]
+    fn test_forecast_aggregate_into_week_work_as_expected() {
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast(), self::create_forecast()], vec![])
+            .into_week_aggregate(&NaiveDate::from_ymd_opt(2019, 3, 1).unwrap(), 5);
+
+        assert_eq!(aggregate.forecast_collection, vec![
+            Forecast::new(
+                vec![(String::from("2019-03-01"), 3.0), (String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0), (String::from("2019-03-04"), 6.0), (String::from("2019-03-05"), 7.0)].into_iter().collect::<RawForecast>(),
+                String::from("test"),
+            ),
+            Forecast::new(
+                vec![(String::from("2019-03-01"), 3.0), (String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0), (String::from("2019-03-04"), 6.0), (String::from("2019-03-05"), 7.0)].into_iter().collect::<RawForecast>(),
+                String::from("test"),
+            )
+        ]);
+
+        assert_eq!(aggregate.warnings.len(), 0);
+    }
+
+    #[test]
+    fn test_forecast_aggregate_into_week_add_warnings_for_invalid_forecasts() {
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast(), self::create_forecast_on_date(String::from("2019-03-05"), 4.0)], vec![])
+            .into_week_aggregate(&NaiveDate::from_ymd_opt(2019, 3, 1).unwrap(), 5);
+
+        assert_eq!(aggregate.forecast_collection, vec![
+            Forecast::new(
+                vec![(String::from("2019-03-01"), 3.0), (String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0), (String::from("2019-03-04"), 6.0), (String::from("2019-03-05"), 7.0)].into_iter().collect::<RawForecast>(),
+                String::from("test"),
+            )
+        ]);
+
+        assert_eq!(aggregate.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_forecast_aggregate_average_forecast() {
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast(), self::create_forecast()], vec![]);
+
+        assert_eq!(aggregate.calculate_average_forecast().unwrap().items, self::create_forecast().items);
+    }
+
+    #[test]
+    fn test_forecast_aggregate_average_forecast_2() {
+        let aggregate = ForecastAggregate::new(vec![
+            self::create_forecast(),
+            Forecast::new(
+                vec![(String::from("2019-03-01"), -3.0), (String::from("2019-03-02"), -4.0), (String::from("2019-03-03"), -5.0), (String::from("2019-03-04"), -6.0), (String::from("2019-03-05"), -7.0), (String::from("2019-03-06"), -8.0), (String::from("2019-03-07"), -9.0)].into_iter().collect::<RawForecast>(),
+                String::from("test"),
+            )
+        ], vec![]);
+
+        assert_eq!(
+            aggregate.calculate_average_forecast().unwrap().items,
+            Forecast::new(
+                vec![(String::from("2019-03-01"), 0.0), (String::from("2019-03-02"), 0.0), (String::from("2019-03-03"), 0.0), (String::from("2019-03-04"), 0.0), (String::from("2019-03-05"), 0.0), (String::from("2019-03-06"), 0.0), (String::from("2019-03-07"), 0.0)].into_iter().collect::<RawForecast>(),
+                String::from("test"),
+            ).items
+        );
+    }
+
+
+    #[test]
+    fn test_forecast_aggregate_filter_by_range_work_as_expected() {
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast(), self::create_forecast()], vec![])
+            .filter_by_range(&NaiveDate::from_ymd_opt(2019, 3, 2).unwrap(), &NaiveDate::from_ymd_opt(2019, 3, 3).unwrap());
+
+        assert_eq!(aggregate.forecast_collection, vec![
+            Forecast::new(
+                vec![(String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0)].into_iter().collect::<RawForecast>(),
+                String::from("test"),
+            ),
+            Forecast::new(
+                vec![(String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0)].into_iter().collect::<RawForecast>(),
+                String::from("test"),
+            )
+        ]);
+
+        assert_eq!(aggregate.warnings.len(), 0);
+    }
+
+    #[test]
+    fn test_forecast_aggregate_filter_by_range_keep_partial_forecasts() {
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast(), self::create_forecast_on_date(String::from("2019-03-05"), 4.0)], vec![])
+            .filter_by_range(&NaiveDate::from_ymd_opt(2019, 3, 5).unwrap(), &NaiveDate::from_ymd_opt(2019, 3, 8).unwrap());
+
+        assert_eq!(aggregate.forecast_collection, vec![
+            Forecast::new(
+                vec![(String::from("2019-03-05"), 7.0), (String::from("2019-03-06"), 8.0), (String::from("2019-03-07"), 9.0)].into_iter().collect::<RawForecast>(),
+                String::from("test"),
+            ),
+            self::create_forecast_on_date(String::from("2019-03-05"), 4.0),
+        ]);
+
+        assert_eq!(aggregate.warnings.len(), 2);
+        assert_eq!(aggregate.warnings[0].code, WarningCode::PartialCoverage);
+        assert_eq!(aggregate.warnings[0].date, Some(String::from("2019-03-08")));
+        assert_eq!(aggregate.warnings[0].message, "Partial coverage for provider test, no forecast on 1 of the dates from 2019-03-08 to 2019-03-08 !");
+        assert_eq!(aggregate.warnings[1].date, Some(String::from("2019-03-06")));
+        assert_eq!(aggregate.warnings[1].message, "Partial coverage for provider test, no forecast on 3 of the dates from 2019-03-06 to 2019-03-08 !");
+    }
+
+    #[test]
+    fn test_forecast_aggregate_filter_by_range_add_warnings_for_uncovered_range() {
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast()], vec![])
+            .filter_by_range(&NaiveDate::from_ymd_opt(2019, 4, 1).unwrap(), &NaiveDate::from_ymd_opt(2019, 4, 3).unwrap());
+
+        assert!(aggregate.forecast_collection.is_empty());
+        assert_eq!(aggregate.warnings.len(), 1);
+        assert_eq!(aggregate.warnings[0].code, WarningCode::UnsupportedRange);
+    }
+
+    #[test]
+    fn test_forecast_aggregate_filter_by_period() {
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast()], vec![]);
+
+        assert_eq!(
+            aggregate.clone().filter_by_period(&Period::Date(NaiveDate::from_ymd_opt(2019, 3, 2).unwrap())).forecast_collection,
+            vec![self::create_forecast_on_date(String::from("2019-03-02"), 4.0)]
+        );
+        assert_eq!(
+            aggregate.clone().filter_by_period(&Period::Range { from: NaiveDate::from_ymd_opt(2019, 3, 7).unwrap(), to: NaiveDate::from_ymd_opt(2019, 3, 9).unwrap() }).warnings.len(),
+            1
+        );
+        assert_eq!(
+            aggregate.filter_by_period(&Period::Week { today: NaiveDate::from_ymd_opt(2019, 3, 4).unwrap(), days: 5 }).warnings.len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_forecast_aggregate_retain_providers() {
+        let other = Forecast::new(vec![(String::from("2019-03-01"), 5.0)].into_iter().collect::<RawForecast>(), String::from("other"));
+        let aggregate = ForecastAggregate::new(vec![self::create_forecast(), other], vec![]);
+        let retained = aggregate.retain_providers(&[String::from("other")]);
+
+        assert_eq!(retained.forecast_collection.len(), 1);
+        assert!(retained.retain_providers(&[]).forecast_collection.is_empty());
+    }
+
+    #[test]
+    fn test_forecast_aggregate_coverage() {
+        let aggregate = ForecastAggregate::new(vec![
+            self::create_forecast(),
+            Forecast::new(vec![(String::from("2019-03-07"), 1.0)].into_iter().collect::<RawForecast>(), String::from("other")),
+        ], vec![]);
+
+        let coverage = aggregate.calculate_coverage();
+        assert_eq!(coverage.get("2019-03-01").unwrap(), &vec![String::from("test")]);
+        assert_eq!(coverage.get("2019-03-07").unwrap(), &vec![String::from("test"), String::from("other")]);
+    }
+
+    #[test]
+    fn test_forecast_aggregate_average_forecast_over_covering_providers_only() {
+        let aggregate = ForecastAggregate::new(vec![
+            self::create_forecast(),
+            Forecast::new(vec![(String::from("2019-03-07"), 1.0)].into_iter().collect::<RawForecast>(), String::from("other")),
+        ], vec![]);
+
+        let average = aggregate.calculate_average_forecast().unwrap();
+        assert_eq!(average.get_temperature_at(String::from("2019-03-01")).unwrap(), &3.0);
+        assert_eq!(average.get_temperature_at(String::from("2019-03-07")).unwrap(), &5.0);
+    }
+
+    #[test]
+    fn test_forecast_aggregate_statistics() {
+        let aggregate = ForecastAggregate::new(vec![
+            self::create_forecast(),
+            Forecast::new(vec![(String::from("2019-03-07"), 1.0)].into_iter().collect::<RawForecast>(), String::from("other")),
+        ], vec![]);
+
+        let statistics = aggregate.calculate_statistics();
+        assert_eq!(statistics.len(), 7);
+
+        let single = statistics.get("2019-03-01").unwrap();
+        assert_eq!((single.mean, single.min, single.max), (3.0, 3.0, 3.0));
+        assert_eq!(single.providers.keys().collect::<Vec<_>>(), vec!["test"]);
+
+        let both = statistics.get("2019-03-07").unwrap();
+        assert_eq!((both.mean, both.min, both.max), (5.0, 1.0, 9.0));
+        assert_eq!(both.providers.get("other"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_forecast_aggregate_average_forecast_return_none_if_no_forecasts() {
+        let aggregate = ForecastAggregate::new(vec![], vec![]);
+
+        assert!(aggregate.calculate_average_forecast().is_none());
+    }
+}
+
+#[cfg(test)]
+mod forecast_test {
+    use crate::forecast::Forecast;
+    use crate::RawForecast;
+    use chrono::NaiveDate;
+
+    fn create_forecast() -> Forecast {
+        Forecast::new(
+            vec![(String::from("2019-03-01"), 3.0), (String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0), (String::from("2019-03-04"), 6.0), (String::from("2019-03-05"), 7.0), (String::from("2019-03-06"), 8.0), (String::from("2019-03-07"), 9.0)].into_iter().collect::<RawForecast>(),
+            String::from("test"),
+        )
+    }
+
+    #[test]
+    fn test_forecast_into_date_work_as_expected() {
+        let forecast = self::create_forecast();
+
+        let date_forecast = forecast.into_date_forecast(String::from("2019-03-02"));
+        assert_eq!(date_forecast.unwrap().items, vec![(String::from("2019-03-02"), 4.0)].into_iter().collect::<RawForecast>());
+    }
+
+    #[test]
+    fn test_forecast_into_date_fail_if_no_date() {
+        let forecast = self::create_forecast();
+
+        let date_forecast = forecast.into_date_forecast(String::from("2019-03-11"));
+        assert!(date_forecast.is_err());
+    }
+
+    #[test]
+    fn test_forecast_into_range_work_as_expected() {
+        let forecast = self::create_forecast();
+
+        let range_forecast = forecast.into_range_forecast(&NaiveDate::from_ymd_opt(2019, 3, 6).unwrap(), &NaiveDate::from_ymd_opt(2019, 3, 10).unwrap());
+        assert_eq!(range_forecast.unwrap().items, vec![(String::from("2019-03-06"), 8.0), (String::from("2019-03-07"), 9.0)].into_iter().collect::<RawForecast>());
+    }
+
+    #[test]
+    fn test_forecast_into_range_fail_if_no_dates() {
+        assert!(self::create_forecast().into_range_forecast(&NaiveDate::from_ymd_opt(2019, 3, 8).unwrap(), &NaiveDate::from_ymd_opt(2019, 3, 10).unwrap()).is_err());
+        assert!(self::create_forecast().into_range_forecast(&NaiveDate::from_ymd_opt(2019, 3, 3).unwrap(), &NaiveDate::from_ymd_opt(2019, 3, 2).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_forecast_into_week_work_as_expected() {
+        let forecast = self::create_forecast();
+
+        let week_forecast = forecast.into_week_forecast(&NaiveDate::from_ymd_opt(2019, 3, 1).unwrap(), 5);
+        assert_eq!(week_forecast.unwrap().items, vec![(String::from("2019-03-01"), 3.0), (String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0), (String::from("2019-03-04"), 6.0), (String::from("2019-03-05"), 7.0)].into_iter().collect::<RawForecast>());
+    }
+
+    #[test]
+    fn test_forecast_into_week_start_at_today() {
+        let forecast = self::create_forecast();
+
+        let week_forecast = forecast.into_week_forecast(&NaiveDate::from_ymd_opt(2019, 3, 2).unwrap(), 3);
+        assert_eq!(week_forecast.unwrap().items, vec![(String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0), (String::from("2019-03-04"), 6.0)].into_iter().collect::<RawForecast>());
+    }
+
+    #[test]
+    fn test_forecast_into_week_fail_if_forecast_end_before_window() {
+        let forecast = self::create_forecast();
+
+        let week_forecast = forecast.into_week_forecast(&NaiveDate::from_ymd_opt(2019, 3, 5).unwrap(), 5);
+        assert!(week_forecast.is_err());
+    }
+
+
+    #[test]
+    fn test_forecast_into_week_fail_if_forecast_to_short() {
+        let forecast = Forecast::new(
+            vec![(String::from("2019-03-01"), 3.0), (String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0)].into_iter().collect::<RawForecast>(),
+            String::from("test"),
+        );
+
+        let week_forecast = forecast.into_week_forecast(&NaiveDate::from_ymd_opt(2019, 3, 1).unwrap(), 5);
+        assert!(week_forecast.is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,344 @@
+use std::collections::{BTreeMap, HashMap};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use chrono_tz::Tz;
+use thiserror::Error;
+
+mod test;
+
+#[derive(Debug, Error)]
+pub enum GeocoderError {
+    #[error("Can't read gazetteer: {}", .reason)]
+    ReadError {
+        reason: String,
+    },
+
+    #[error("Invalid gazetteer line {} !", .line)]
+    InvalidLine {
+        line: usize,
+    },
+}
+
+/// Populated place from the gazetteer, the canonical form of a location.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Place {
+    pub id: u64,
+    pub name: String,
+    pub country_code: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub population: u64,
+    pub timezone: String,
+}
+
+impl Place {
+    pub fn tz(&self) -> Option<Tz> {
+        self.timezone.parse().ok()
+    }
+}
+
+/// Place offered to the client when the query can't be resolved to a single place.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Candidate {
+    pub id: u64,
+    pub name: String,
+    pub country_code: String,
+    pub population: u64,
+    pub lat: f64,
+    pub lon: f64,
+    pub similarity: f64,
+}
+
+impl Candidate {
+    fn new(place: &Place, similarity: f64) -> Self {
+        Candidate {
+            id: place.id,
+            name: place.name.clone(),
+            country_code: place.country_code.clone(),
+            population: place.population,
+            lat: place.lat,
+            lon: place.lon,
+            similarity,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Resolution<'a> {
+    Found(&'a Place),
+    /// Several places share the name and none of them clearly dominates.
+    Ambiguous(Vec<Candidate>),
+    /// Nothing with this name, candidates are similarly spelled places (may be empty).
+    NotFound(Vec<Candidate>),
+}
+
+/// In-memory index over a GeoNames cities dump (http://download.geonames.org/export/dump/, e.g. cities15000.txt).
+pub struct Geocoder {
+    places: Vec<Place>,
+    name_index: HashMap<String, Vec<usize>>,
+    id_index: HashMap<u64, usize>,
+    /// Names of `name_index` by their length in chars, `suggest` only compares names of a similar length.
+    length_index: BTreeMap<usize, Vec<String>>,
+}
+
+impl Geocoder {
+    const COLUMNS_COUNT: usize = 19;
+    /// The most populated place wins without asking when it is this many times bigger than the next one.
+    const DOMINANCE_RATIO: u64 = 10;
+    const MIN_SIMILARITY: f64 = 0.7;
+    const MAX_CANDIDATES: usize = 10;
+    /// Longer names get no suggestions, real place names are shorter.
+    const MAX_SUGGEST_LEN: usize = 64;
+    /// Names compared by one `suggest` call at most, those of the closest lengths first.
+    const MAX_SUGGEST_SCAN: usize = 20_000;
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GeocoderError> {
+        let file = File::open(path).map_err(|e| GeocoderError::ReadError { reason: e.to_string() })?;
+
+        Self::from_reader(BufReader::new(file))
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, GeocoderError> {
+        let mut geocoder = Geocoder { places: Vec::new(), name_index: HashMap::new(), id_index: HashMap::new(), length_index: BTreeMap::new() };
+
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| GeocoderError::ReadError { reason: e.to_string() })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split('\t').collect();
+            let place = Self::parse_place(&columns).ok_or(GeocoderError::InvalidLine { line: line_no + 1 })?;
+
+            let names = vec![columns[1], columns[2]]
+                .into_iter()
+                .chain(columns[3].split(','));
+            geocoder.insert(place, names);
+        }
+
+        Ok(geocoder)
+    }
+
+    fn parse_place(columns: &[&str]) -> Option<Place> {
+        if columns.len() < Self::COLUMNS_COUNT {
+            return None;
+        }
+
+        Some(Place {
+            id: columns[0].parse().ok()?,
+            name: columns[1].to_owned(),
+            country_code: columns[8].to_uppercase(),
+            lat: columns[4].parse().ok()?,
+            lon: columns[5].parse().ok()?,
+            population: columns[14].parse().unwrap_or(0),
+            timezone: columns[17].to_owned(),
+        })
+    }
+
+    fn insert<'a, I: Iterator<Item=&'a str>>(&mut self, place: Place, names: I) {
+        let idx = self.places.len();
+        self.id_index.insert(place.id, idx);
+        self.places.push(place);
+
+        for name in names {
+            let key = normalize(name);
+            if key.is_empty() {
+                continue;
+            }
+
+            if !self.name_index.contains_key(&key) {
+                self.length_index.entry(key.chars().count()).or_default().push(key.clone());
+            }
+
+            let entries = self.name_index.entry(key).or_default();
+            if !entries.contains(&idx) {
+                entries.push(idx);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.places.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.places.is_empty()
+    }
+
+    /// All places known under the name (or one of its alternate names), most populated first.
+    pub fn find(&self, name: &str, country_code: Option<&str>) -> Vec<&Place> {
+        let mut places: Vec<&Place> = self.name_index
+            .get(&normalize(name))
+            .map(|entries| entries.iter().map(|idx| &self.places[*idx]).collect())
+            .unwrap_or_default();
+
+        if let Some(country_code) = country_code {
+            places.retain(|place| place.country_code.eq_ignore_ascii_case(country_code.trim()));
+        }
+
+        places.sort_by(|a, b| b.population.cmp(&a.population).then(a.id.cmp(&b.id)));
+        places
+    }
+
+    /// Resolves free text like `Moscow`, `Москва` or `Paris, FR` to the most populated matching place.
+    pub fn resolve(&self, query: &str) -> Option<&Place> {
+        let (name, country_code) = split_country(query);
+
+        self.find(name, country_code).into_iter().next()
+    }
+
+    /// Like `resolve`, but tells apart ambiguous names and unknown ones (with spelling suggestions).
+    pub fn lookup(&self, query: &str, country_code: Option<&str>) -> Resolution<'_> {
+        let (name, query_country_code) = split_country(query);
+        let country_code = country_code.or(query_country_code);
+
+        let places = self.find(name, country_code);
+
+        match places.len() {
+            0 => Resolution::NotFound(self.suggest(name, country_code)),
+            1 => Resolution::Found(places[0]),
+            _ if places[0].population >= places[1].population.saturating_mul(Self::DOMINANCE_RATIO) => Resolution::Found(places[0]),
+            _ => Resolution::Ambiguous(
+                places.into_iter().take(Self::MAX_CANDIDATES).map(|place| Candidate::new(place, 1.0)).collect()
+            ),
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Place> {
+        self.id_index.get(&id).map(|idx| &self.places[*idx])
+    }
+
+    /// Places with names similar to the given one, the most similar (then the most populated) first.
+    ///
+    /// Compares at most `MAX_SUGGEST_SCAN` names, so unknown names cost the same whatever the gazetteer size.
+    pub fn suggest(&self, name: &str, country_code: Option<&str>) -> Vec<Candidate> {
+        let needle: Vec<char> = normalize(name).chars().collect();
+        if needle.is_empty() || needle.len() > Self::MAX_SUGGEST_LEN {
+            return Vec::new();
+        }
+
+        let mut best: HashMap<usize, f64> = HashMap::new();
+        for (key_len, key) in self.similar_length_names(needle.len()).take(Self::MAX_SUGGEST_SCAN) {
+            let max_len = std::cmp::max(key_len, needle.len()) as f64;
+            let max_distance = ((1.0 - Self::MIN_SIMILARITY) * max_len) as usize;
+
+            let similarity = match levenshtein(&needle, key, max_distance) {
+                Some(distance) => 1.0 - distance as f64 / max_len,
+                None => continue,
+            };
+            if similarity < Self::MIN_SIMILARITY {
+                continue;
+            }
+
+            for idx in &self.name_index[key] {
+                let entry = best.entry(*idx).or_insert(similarity);
+                if *entry < similarity {
+                    *entry = similarity;
+                }
+            }
+        }
+
+        let mut candidates: Vec<Candidate> = best
+            .into_iter()
+            .map(|(idx, similarity)| Candidate::new(&self.places[idx], similarity))
+            .filter(|candidate| match country_code {
+                Some(country_code) => candidate.country_code.eq_ignore_ascii_case(country_code.trim()),
+                None => true,
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| {
+            b.similarity.partial_cmp(&a.similarity).unwrap_or(Ordering::Equal)
+                .then(b.population.cmp(&a.population))
+                .then(a.id.cmp(&b.id))
+        });
+        candidates.truncate(Self::MAX_CANDIDATES);
+
+        candidates
+    }
+
+    /// Names with `len` chars or a length close enough to reach `MIN_SIMILARITY`, the closest lengths first.
+    fn similar_length_names(&self, len: usize) -> impl Iterator<Item=(usize, &String)> {
+        let min_len = (len as f64 * Self::MIN_SIMILARITY).ceil() as usize;
+        let max_len = (len as f64 / Self::MIN_SIMILARITY).floor() as usize;
+
+        let mut lengths: Vec<usize> = self.length_index.range(min_len..=max_len).map(|(key_len, _)| *key_len).collect();
+        lengths.sort_by_key(|key_len| (*key_len as isize - len as isize).abs());
+
+        lengths
+            .into_iter()
+            .flat_map(move |key_len| self.length_index[&key_len].iter().map(move |key| (key_len, key)))
+    }
+
+    pub fn nearest(&self, lat: f64, lon: f64) -> Option<&Place> {
+        self.places
+            .iter()
+            .map(|place| (place, squared_distance(place, lat, lon)))
+            .fold(None, |nearest: Option<(&Place, f64)>, (place, distance)| match nearest {
+                Some((_, nearest_distance)) if nearest_distance <= distance => nearest,
+                _ => Some((place, distance)),
+            })
+            .map(|(place, _)| place)
+    }
+}
+
+/// Splits trailing two-letter country code: `Paris, FR` -> (`Paris`, Some(`FR`)).
+pub fn split_country(query: &str) -> (&str, Option<&str>) {
+    if let Some(pos) = query.rfind(',') {
+        let country_code = query[pos + 1..].trim();
+        if country_code.len() == 2 && country_code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return (query[..pos].trim(), Some(country_code));
+        }
+    }
+
+    (query.trim(), None)
+}
+
+pub fn normalize(name: &str) -> String {
+    name
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Edit distance between `a` and `b` when it is at most `limit`, stops as soon as it can only be bigger.
+fn levenshtein(a: &[char], b: &str, limit: usize) -> Option<usize> {
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > limit {
+        return None;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + if a_char == b_char { 0 } else { 1 };
+            current[j + 1] = std::cmp::min(substitution, std::cmp::min(prev[j + 1], current[j]) + 1);
+        }
+        if current.iter().min().is_some_and(|min| *min > limit) {
+            return None;
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    Some(prev[b.len()]).filter(|distance| *distance <= limit)
+}
+
+/// Equirectangular approximation, good enough to compare distances between nearby places.
+fn squared_distance(place: &Place, lat: f64, lon: f64) -> f64 {
+    let mut d_lon = (place.lon - lon).abs();
+    if d_lon > 180.0 {
+        d_lon = 360.0 - d_lon;
+    }
+
+    let x = d_lon.to_radians() * ((place.lat + lat) / 2.0).to_radians().cos();
+    let y = (place.lat - lat).to_radians();
+
+    x * x + y * y
+}
@@ -0,0 +1,143 @@
+#![cfg(test)]
+
+use super::*;
+
+const GAZETTEER: &str = concat!(
+    "524901\tMoscow\tMoscow\tMoscou,Moskau,Moskva,Москва\t55.75222\t37.61556\tP\tPPLC\tRU\t\t48\t\t\t\t10381222\t\t144\tEurope/Moscow\t2019-03-20\n",
+    "5601538\tMoscow\tMoscow\t\t46.73239\t-117.00017\tP\tPPLA2\tUS\t\tID\t057\t\t\t25435\t786\t788\tAmerica/Los_Angeles\t2017-03-09\n",
+    "2988507\tParis\tParis\tParigi,Париж\t48.85341\t2.3488\tP\tPPLC\tFR\t\t11\t75\t751\t75056\t2138551\t\t42\tEurope/Paris\t2019-01-10\n",
+    "4717560\tParis\tParis\t\t33.66094\t-95.55551\tP\tPPLA2\tUS\t\tTX\t277\t\t\t24782\t184\t183\tAmerica/Chicago\t2017-03-09\n",
+    "4409896\tSpringfield\tSpringfield\t\t37.21533\t-93.29824\tP\tPPLA2\tUS\t\tMO\t077\t\t\t166810\t396\t393\tAmerica/Chicago\t2017-05-23\n",
+    "4250542\tSpringfield\tSpringfield\t\t39.80172\t-89.64371\tP\tPPLA\tUS\t\tIL\t167\t\t\t116565\t182\t184\tAmerica/Chicago\t2017-05-23\n",
+    "578072\tBelgorod\tBelgorod\tБелгород\t50.61074\t36.58015\tP\tPPLA\tRU\t\t09\t\t\t\t345289\t\t135\tEurope/Moscow\t2019-09-05\n",
+);
+
+fn create_geocoder() -> Geocoder {
+    Geocoder::from_reader(GAZETTEER.as_bytes()).unwrap()
+}
+
+#[test]
+fn test_geocoder_load_all_places() {
+    assert_eq!(create_geocoder().len(), 7);
+}
+
+#[test]
+fn test_geocoder_resolve_most_populated() {
+    let geocoder = create_geocoder();
+    let place = geocoder.resolve("Moscow").unwrap();
+
+    assert_eq!(place.id, 524901);
+    assert_eq!(place.country_code, "RU");
+    assert_eq!(place.tz(), Some(chrono_tz::Europe::Moscow));
+}
+
+#[test]
+fn test_geocoder_resolve_alternate_and_cyrillic_names() {
+    let geocoder = create_geocoder();
+
+    assert_eq!(geocoder.resolve("Москва").unwrap().id, 524901);
+    assert_eq!(geocoder.resolve("moskau").unwrap().id, 524901);
+    assert_eq!(geocoder.resolve("  Белгород ").unwrap().id, 578072);
+}
+
+#[test]
+fn test_geocoder_resolve_with_country_code() {
+    let geocoder = create_geocoder();
+
+    assert_eq!(geocoder.resolve("Moscow, US").unwrap().id, 5601538);
+    assert_eq!(geocoder.resolve("Paris,fr").unwrap().id, 2988507);
+    assert!(geocoder.resolve("Paris, DE").is_none());
+}
+
+#[test]
+fn test_geocoder_find_sorted_by_population() {
+    let geocoder = create_geocoder();
+    let places = geocoder.find("Paris", None);
+
+    assert_eq!(places.iter().map(|p| p.id).collect::<Vec<u64>>(), vec![2988507, 4717560]);
+}
+
+#[test]
+fn test_geocoder_unknown_name() {
+    assert!(create_geocoder().resolve("UnknownCityInUnknownCountry").is_none());
+}
+
+#[test]
+fn test_geocoder_nearest() {
+    let geocoder = create_geocoder();
+
+    assert_eq!(geocoder.nearest(55.7, 37.5).unwrap().id, 524901);
+    assert_eq!(geocoder.nearest(50.5, 36.5).unwrap().id, 578072);
+}
+
+#[test]
+fn test_geocoder_fail_on_invalid_line() {
+    assert!(Geocoder::from_reader("524901\tMoscow\n".as_bytes()).is_err());
+}
+
+#[test]
+fn test_geocoder_lookup_dominant_place() {
+    match create_geocoder().lookup("Moscow", None) {
+        Resolution::Found(place) => assert_eq!(place.id, 524901),
+        other => panic!("Unexpected resolution {:?}", other),
+    }
+}
+
+#[test]
+fn test_geocoder_lookup_ambiguous() {
+    match create_geocoder().lookup("Springfield", None) {
+        Resolution::Ambiguous(candidates) => assert_eq!(candidates.iter().map(|c| c.id).collect::<Vec<u64>>(), vec![4409896, 4250542]),
+        other => panic!("Unexpected resolution {:?}", other),
+    }
+}
+
+#[test]
+fn test_geocoder_lookup_not_found_with_suggestions() {
+    match create_geocoder().lookup("Mosow", None) {
+        Resolution::NotFound(candidates) => {
+            assert_eq!(candidates[0].id, 524901);
+            assert_eq!(candidates[1].id, 5601538);
+            assert!(candidates[0].similarity < 1.0);
+        }
+        other => panic!("Unexpected resolution {:?}", other),
+    }
+}
+
+#[test]
+fn test_geocoder_lookup_not_found_without_suggestions() {
+    match create_geocoder().lookup("UnknownCityInUnknownCountry", None) {
+        Resolution::NotFound(candidates) => assert!(candidates.is_empty()),
+        other => panic!("Unexpected resolution {:?}", other),
+    }
+}
+
+#[test]
+fn test_geocoder_get_by_id() {
+    assert_eq!(create_geocoder().get(4250542).unwrap().name, "Springfield");
+    assert!(create_geocoder().get(1).is_none());
+}
+
+#[test]
+fn test_geocoder_suggest_nothing_for_overlong_name() {
+    let name = format!("Moscow{}", "w".repeat(Geocoder::MAX_SUGGEST_LEN));
+
+    assert!(create_geocoder().suggest(&name, None).is_empty());
+}
+
+#[test]
+fn test_geocoder_similar_length_names() {
+    let lengths: Vec<usize> = create_geocoder().similar_length_names(5).map(|(len, _)| len).collect();
+
+    assert!(!lengths.is_empty());
+    assert!(lengths.iter().all(|len| (4..=7).contains(len)));
+    assert!(lengths.windows(2).all(|pair| pair[0].abs_diff(5) <= pair[1].abs_diff(5)));
+}
+
+#[test]
+fn test_levenshtein() {
+    assert_eq!(levenshtein(&"mosow".chars().collect::<Vec<char>>(), "moscow", 3), Some(1));
+    assert_eq!(levenshtein(&"".chars().collect::<Vec<char>>(), "abc", 3), Some(3));
+    assert_eq!(levenshtein(&"москва".chars().collect::<Vec<char>>(), "москва", 0), Some(0));
+    assert_eq!(levenshtein(&"paris".chars().collect::<Vec<char>>(), "moscow", 3), None);
+    assert_eq!(levenshtein(&"ab".chars().collect::<Vec<char>>(), "abcdef", 3), None);
+}
@@ -0,0 +1,358 @@
+//! Weather forecast aggregation over several providers, independent of any web framework.
+//!
+//! Providers talk HTTP through `transport::HttpTransport`, `transport::ReqwestTransport` (default
+//! `reqwest-transport` feature) is the one used by the service:
+//!
+//! ```ignore
+//! let transport: Arc<dyn HttpTransport> = Arc::new(ReqwestTransport::default());
+//! let manager = WeatherAggregateManager::new(vec![Box::new(ApixuProvider::new(transport, key))]);
+//!
+//! let forecast = manager
+//!     .get_forecast_aggregate_on_week(&Location::City(String::from("Moscow")), today, 5)
+//!     .await
+//!     .calculate_average_forecast();
+//! ```
+
+extern crate futures;
+extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate async_trait;
+extern crate thiserror;
+extern crate rand;
+extern crate crypto;
+extern crate base64;
+extern crate url;
+extern crate chrono;
+extern crate chrono_tz;
+extern crate tracing;
+
+use self::provider::WeatherProvider;
+use std::collections::BTreeMap;
+use futures::future::join_all;
+use crate::forecast::ForecastAggregate;
+use crate::location::Location;
+use chrono::NaiveDate;
+use crate::current::CurrentConditionsAggregate;
+use crate::date::Period;
+use crate::provider::capabilities::ProviderCapabilities;
+use crate::warning::Warning;
+use tracing::field::Empty;
+
+pub mod provider;
+pub mod oauth;
+pub mod forecast;
+pub mod warning;
+pub mod location;
+pub mod geocoder;
+pub mod date;
+pub mod current;
+pub mod transport;
+
+pub type Date = String;
+pub type Temperature = f64;
+
+type RawForecast = BTreeMap<Date, Temperature>;
+
+pub struct WeatherAggregateManager {
+    providers: Vec<Box<dyn WeatherProvider>>
+}
+
+impl WeatherAggregateManager {
+    pub fn new(providers: Vec<Box<dyn WeatherProvider>>) -> Self {
+        WeatherAggregateManager {
+            providers
+        }
+    }
+
+    pub fn get_capabilities(&self) -> Vec<ProviderCapabilities> {
+        self.providers.iter().map(|provider| provider.capabilities()).collect()
+    }
+
+    pub async fn get_forecast_aggregate_at(&self, location: &Location, date: NaiveDate, today: NaiveDate) -> ForecastAggregate {
+        self.get_forecast_aggregate_in_period(location, Period::Date(date), today).await
+    }
+
+    pub async fn get_forecast_aggregate_on_week(&self, location: &Location, today: NaiveDate, days: u32) -> ForecastAggregate {
+        self.get_forecast_aggregate_in_period(location, Period::Week { today, days }, today).await
+    }
+
+    pub async fn get_forecast_aggregate_in_range(&self, location: &Location, from: NaiveDate, to: NaiveDate, today: NaiveDate) -> ForecastAggregate {
+        self.get_forecast_aggregate_in_period(location, Period::Range { from, to }, today).await
+    }
+
+    /// Asks only the providers able to forecast some of the period, others are skipped with a warning.
+    ///
+    /// Runs in a `forecast_aggregate` span, the provider calls of the service are its children.
+    #[tracing::instrument(
+        name = "forecast_aggregate",
+        skip_all,
+        fields(location = %location, from = %date::format_date(&period.bounds().0), to = %date::format_date(&period.bounds().1), providers = Empty, skipped = Empty, warnings = Empty),
+    )]
+    pub async fn get_forecast_aggregate_in_period(&self, location: &Location, period: Period, today: NaiveDate) -> ForecastAggregate {
+        let providers = self
+            .providers
+            .iter()
+            .filter(|provider| provider.capabilities().can_forecast(location, &period, &today))
+            .map(|provider| provider.as_ref())
+            .collect::<Vec<&dyn WeatherProvider>>();
+        let skipped = self.get_forecast_skip_warnings(location, &period, &today);
+
+        // Counts as i64, unsigned values are exported to OpenTelemetry as strings.
+        let span = tracing::Span::current();
+        span.record("providers", providers.len() as i64);
+        span.record("skipped", skipped.len() as i64);
+
+        let aggregate = Self::join_forecasts(providers, location)
+            .await
+            .filter_by_period(&period)
+            .with_warning_collection(skipped);
+
+        span.record("warnings", aggregate.get_warnings().len() as i64);
+        aggregate
+    }
+
+    /// Whole forecasts of every provider accepting the location, to be filtered by the caller.
+    #[tracing::instrument(name = "forecast_aggregate", skip_all, fields(location = %location, providers = Empty, warnings = Empty))]
+    pub async fn get_forecast_aggregate(&self, location: &Location) -> ForecastAggregate {
+        let providers = self.providers_accepting(location);
+        tracing::Span::current().record("providers", providers.len() as i64);
+
+        let aggregate = Self::join_forecasts(providers, location).await;
+
+        tracing::Span::current().record("warnings", aggregate.get_warnings().len() as i64);
+        aggregate
+    }
+
+    /// Names of the providers `get_forecast_aggregate_in_period` would ask.
+    pub fn get_forecast_provider_names(&self, location: &Location, period: &Period, today: &NaiveDate) -> Vec<String> {
+        self.get_capabilities()
+            .into_iter()
+            .filter(|capabilities| capabilities.can_forecast(location, period, today))
+            .map(|capabilities| capabilities.name)
+            .collect()
+    }
+
+    /// A warning for every provider `get_forecast_aggregate_in_period` would skip.
+    pub fn get_forecast_skip_warnings(&self, location: &Location, period: &Period, today: &NaiveDate) -> Vec<Warning> {
+        self.get_capabilities()
+            .into_iter()
+            .filter_map(|capabilities| capabilities.skip_warning(location, period, today))
+            .collect()
+    }
+
+    #[tracing::instrument(name = "current_aggregate", skip_all, fields(location = %location))]
+    pub async fn get_current_conditions_aggregate(&self, location: &Location) -> CurrentConditionsAggregate {
+        join_all(
+            self
+                .providers_accepting(location)
+                .into_iter()
+                .map(|provider| provider.get_current_conditions(location))
+        )
+            .await
+            .into_iter()
+            .fold(CurrentConditionsAggregate::empty(), |aggregate, observation_opt| {
+                aggregate.with_observation_result(observation_opt)
+            })
+    }
+
+    fn providers_accepting(&self, location: &Location) -> Vec<&dyn WeatherProvider> {
+        self
+            .providers
+            .iter()
+            .filter(|provider| provider.capabilities().accepts(location))
+            .map(|provider| provider.as_ref())
+            .collect()
+    }
+
+    async fn join_forecasts(providers: Vec<&dyn WeatherProvider>, location: &Location) -> ForecastAggregate {
+        join_all(providers.into_iter().map(|provider| provider.get_forecast(location)))
+            .await
+            .into_iter()
+            .fold(ForecastAggregate::empty(), |aggregate, forecast_opt| {
+                aggregate.with_forecast_result(forecast_opt)
+            })
+    }
+}
+
+
+#[cfg(test)]
+mod manager_test {
+    use crate::forecast::Forecast;
+    use crate::RawForecast;
+    use crate::provider::WeatherProvider;
+    use futures::executor::block_on;
+    use async_trait::async_trait;
+    use crate::provider::ProviderError;
+    use crate::location::Location;
+    use crate::current::CurrentConditions;
+    use crate::location::LocationType;
+    use crate::date::Period;
+    use crate::provider::capabilities::{ProviderCapabilities, Variable};
+    use crate::warning::WarningCode;
+    use chrono::NaiveDate;
+    use super::*;
+
+    fn create_forecast() -> Forecast {
+        Forecast::new(
+            vec![(String::from("2019-03-01"), 3.0), (String::from("2019-03-02"), 4.0), (String::from("2019-03-03"), 5.0), (String::from("2019-03-04"), 6.0), (String::from("2019-03-05"), 7.0), (String::from("2019-03-06"), 8.0), (String::from("2019-03-07"), 9.0)].into_iter().collect::<RawForecast>(),
+            String::from("provider_stub"),
+        )
+    }
+
+    struct ProviderStub {
+        horizon_days: u32,
+        location_types: Vec<LocationType>,
+    }
+
+    impl ProviderStub {
+        fn new() -> Self {
+            ProviderStub {
+                horizon_days: 10,
+                location_types: vec![LocationType::City, LocationType::Coordinates],
+            }
+        }
+    }
+
+    #[async_trait]
+    impl WeatherProvider for ProviderStub {
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                name: String::from("provider_stub"),
+                horizon_days: self.horizon_days,
+                hourly: false,
+                variables: vec![Variable::Temperature],
+                location_types: self.location_types.clone(),
+                rate_limit: None,
+            }
+        }
+
+        async fn get_forecast(&self, _location: &Location) -> Result<Forecast, ProviderError> {
+            Ok(self::create_forecast())
+        }
+
+        async fn get_current_conditions(&self, _location: &Location) -> Result<CurrentConditions, ProviderError> {
+            Ok(CurrentConditions {
+                provider: String::from("provider_stub"),
+                observed_at: 1553600000,
+                temperature: 3.0,
+                humidity: None,
+                wind_kph: None,
+                condition: None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_get_at_date_work_as_expected() {
+        let wam = WeatherAggregateManager::new(vec![
+            Box::new(ProviderStub::new()),
+            Box::new(ProviderStub::new())
+        ]);
+
+        let result = block_on(
+            wam.get_forecast_aggregate_at(&Location::City(String::from("location")), NaiveDate::from_ymd_opt(2019, 3, 1).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 1).unwrap())
+        );
+
+        assert_eq!(
+            result.calculate_average_forecast().unwrap().get_temperature_at(String::from("2019-03-01")).unwrap(),
+            &3.0
+        );
+    }
+
+    #[test]
+    fn test_get_on_week_work_as_expected() {
+        let wam = WeatherAggregateManager::new(vec![
+            Box::new(ProviderStub::new()),
+            Box::new(ProviderStub::new())
+        ]);
+
+        let result = block_on(wam.get_forecast_aggregate_on_week(&Location::City(String::from("location")), NaiveDate::from_ymd_opt(2019, 3, 1).unwrap(), 5));
+
+        let forecast = result.calculate_average_forecast().unwrap();
+        assert_eq!(
+            forecast.get_temperature_at(String::from("2019-03-01")).unwrap(),
+            &3.0
+        );
+        assert_eq!(
+            forecast.get_temperature_at(String::from("2019-03-05")).unwrap(),
+            &7.0
+        );
+    }
+
+    #[test]
+    fn test_get_in_range_work_as_expected() {
+        let wam = WeatherAggregateManager::new(vec![
+            Box::new(ProviderStub::new()),
+            Box::new(ProviderStub::new())
+        ]);
+
+        let result = block_on(
+            wam.get_forecast_aggregate_in_range(&Location::City(String::from("location")), NaiveDate::from_ymd_opt(2019, 3, 6).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 9).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 1).unwrap())
+        );
+
+        let forecast = result.calculate_average_forecast().unwrap();
+        assert_eq!(forecast.get_temperature_at(String::from("2019-03-06")).unwrap(), &8.0);
+        assert_eq!(forecast.get_temperature_at(String::from("2019-03-07")).unwrap(), &9.0);
+        assert_eq!(result.get_warnings().len(), 2);
+    }
+
+    #[test]
+    fn test_get_current_conditions_work_as_expected() {
+        let wam = WeatherAggregateManager::new(vec![
+            Box::new(ProviderStub::new()),
+            Box::new(ProviderStub::new())
+        ]);
+
+        let result = block_on(wam.get_current_conditions_aggregate(&Location::City(String::from("location"))));
+
+        assert_eq!(result.get_observations().len(), 2);
+        assert_eq!(result.calculate_average_conditions().unwrap().temperature, 3.0);
+    }
+
+    #[test]
+    fn test_providers_beyond_horizon_are_skipped() {
+        let wam = WeatherAggregateManager::new(vec![
+            Box::new(ProviderStub::new()),
+            Box::new(ProviderStub { horizon_days: 3, ..ProviderStub::new() })
+        ]);
+
+        let today = NaiveDate::from_ymd_opt(2019, 3, 1).unwrap();
+        let result = block_on(wam.get_forecast_aggregate_in_range(&Location::City(String::from("location")), NaiveDate::from_ymd_opt(2019, 3, 4).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 5).unwrap(), today));
+
+        assert_eq!(result.calculate_coverage()[&String::from("2019-03-05")].len(), 1);
+        assert_eq!(result.get_warnings().len(), 1);
+        assert_eq!(result.get_warnings()[0].code, WarningCode::BeyondHorizon);
+        assert_eq!(result.get_warnings()[0].date, Some(String::from("2019-03-04")));
+    }
+
+    #[test]
+    fn test_providers_overlapping_horizon_are_asked() {
+        let wam = WeatherAggregateManager::new(vec![
+            Box::new(ProviderStub::new()),
+            Box::new(ProviderStub { horizon_days: 3, ..ProviderStub::new() })
+        ]);
+
+        let today = NaiveDate::from_ymd_opt(2019, 3, 1).unwrap();
+        let result = block_on(wam.get_forecast_aggregate_in_range(&Location::City(String::from("location")), NaiveDate::from_ymd_opt(2019, 3, 3).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 5).unwrap(), today));
+
+        assert_eq!(result.provider_count(), 2);
+        assert!(result.get_warnings().iter().all(|warning| warning.code != WarningCode::BeyondHorizon));
+    }
+
+    #[test]
+    fn test_providers_not_accepting_location_are_skipped() {
+        let wam = WeatherAggregateManager::new(vec![
+            Box::new(ProviderStub::new()),
+            Box::new(ProviderStub { location_types: vec![LocationType::Airport], ..ProviderStub::new() })
+        ]);
+
+        let location = Location::City(String::from("location"));
+
+        assert_eq!(block_on(wam.get_current_conditions_aggregate(&location)).get_observations().len(), 1);
+
+        let result = block_on(wam.get_forecast_aggregate_at(&location, NaiveDate::from_ymd_opt(2019, 3, 1).unwrap(), NaiveDate::from_ymd_opt(2019, 3, 1).unwrap()));
+        assert_eq!(result.provider_count(), 1);
+        assert_eq!(result.get_warnings().iter().map(|warning| warning.code).collect::<Vec<WarningCode>>(), vec![WarningCode::UnsupportedLocation]);
+        assert_eq!(wam.get_forecast_provider_names(&location, &Period::Date(NaiveDate::from_ymd_opt(2019, 3, 1).unwrap()), &NaiveDate::from_ymd_opt(2019, 3, 1).unwrap()).len(), 1);
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt;
+use chrono_tz::Tz;
+use crate::geocoder::{Geocoder, Place, Candidate, Resolution};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LocationError {
+    #[error("Empty location !")]
+    EmptyLocation {},
+
+    #[error("Invalid coordinates lat: {}, lon: {} !", .lat, .lon)]
+    InvalidCoordinates {
+        lat: String,
+        lon: String,
+    },
+
+    #[error("Both lat and lon expected, {} is missing !", .missing)]
+    IncompleteCoordinates {
+        missing: String,
+    },
+
+    #[error("Invalid id param: {} !", .id)]
+    InvalidPlaceId {
+        id: String,
+    },
+
+    #[error("Invalid airport code {} !", .code)]
+    InvalidAirportCode {
+        code: String,
+    },
+
+    #[error("Ambiguous location {} !", .query)]
+    AmbiguousLocation {
+        query: String,
+        candidates: Vec<Candidate>,
+    },
+
+    #[error("Unknown location {} !", .query)]
+    UnknownLocation {
+        query: String,
+        candidates: Vec<Candidate>,
+    },
+}
+
+/// Kind of the location, providers declare the kinds they accept.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum LocationType {
+    City,
+    Coordinates,
+    PostalCode,
+    Airport,
+    Place,
+}
+
+/// Location the forecast is asked for, as it comes from the client.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    City(String),
+    Coordinates {
+        lat: f64,
+        lon: f64,
+    },
+    PostalCode(String),
+    Airport(String),
+    Place(Place),
+}
+
+impl Location {
+    const AIRPORT_PREFIX: &'static str = "iata:";
+    const POSTAL_CODE_PREFIX: &'static str = "zip:";
+
+    /// Parses location from the path segment, `lat` and `lon` query params take precedence over it
+    /// and are expected together.
+    ///
+    /// Path segment formats: `Moscow`, `55.75,37.61`, `zip:101000` (or just digits), `iata:SVO`.
+    pub fn parse(path: &str, lat: Option<&str>, lon: Option<&str>) -> Result<Self, LocationError> {
+        match (lat, lon) {
+            (Some(lat), Some(lon)) => return Self::parse_coordinates(lat, lon),
+            (Some(_), None) => return Err(LocationError::IncompleteCoordinates { missing: String::from("lon") }),
+            (None, Some(_)) => return Err(LocationError::IncompleteCoordinates { missing: String::from("lat") }),
+            (None, None) => {}
+        }
+
+        let path = path.trim();
+        if path.is_empty() {
+            return Err(LocationError::EmptyLocation {});
+        }
+
+        if let Some(code) = Self::strip_prefix(path, Self::AIRPORT_PREFIX) {
+            return Self::parse_airport(code);
+        }
+
+        if let Some(code) = Self::strip_prefix(path, Self::POSTAL_CODE_PREFIX) {
+            let code = code.trim();
+            return match code.is_empty() {
+                true => Err(LocationError::EmptyLocation {}),
+                _ => Ok(Location::PostalCode(code.to_owned())),
+            };
+        }
+
+        if path.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(Location::PostalCode(path.to_owned()));
+        }
+
+        let parts: Vec<&str> = path.split(',').collect();
+        if parts.len() == 2 && parts.iter().all(|p| p.trim().parse::<f64>().is_ok()) {
+            return Self::parse_coordinates(parts[0], parts[1]);
+        }
+
+        Ok(Location::City(path.to_owned()))
+    }
+
+    /// Replaces city name with the place found in the gazetteer, other locations are kept as is.
+    ///
+    /// Ambiguous names and misspelled ones (those having similar names in the gazetteer) are errors,
+    /// names the gazetteer knows nothing about go to providers as is.
+    pub fn canonicalize(self, geocoder: &Geocoder, country_code: Option<&str>) -> Result<Self, LocationError> {
+        match self {
+            Location::City(name) => match geocoder.lookup(&name, country_code) {
+                Resolution::Found(place) => Ok(Location::Place(place.clone())),
+                Resolution::Ambiguous(candidates) => Err(LocationError::AmbiguousLocation { query: name, candidates }),
+                Resolution::NotFound(ref candidates) if candidates.is_empty() => Ok(Location::City(name)),
+                Resolution::NotFound(candidates) => Err(LocationError::UnknownLocation { query: name, candidates }),
+            },
+            location => Ok(location),
+        }
+    }
+
+    /// Place picked by its gazetteer id, e.g. one of the candidates offered earlier.
+    pub fn from_place_id(geocoder: &Geocoder, id: u64) -> Result<Self, LocationError> {
+        geocoder
+            .get(id)
+            .map(|place| Location::Place(place.clone()))
+            .ok_or(LocationError::UnknownLocation { query: id.to_string(), candidates: Vec::new() })
+    }
+
+    /// Identifies the location, e.g. to share one provider request between equal locations.
+    pub fn key(&self) -> String {
+        match self {
+            Location::Place(place) => format!("place:{}", place.id),
+            location => location.to_string(),
+        }
+    }
+
+    pub fn location_type(&self) -> LocationType {
+        match self {
+            Location::City(_) => LocationType::City,
+            Location::Coordinates { .. } => LocationType::Coordinates,
+            Location::PostalCode(_) => LocationType::PostalCode,
+            Location::Airport(_) => LocationType::Airport,
+            Location::Place(_) => LocationType::Place,
+        }
+    }
+
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        match self {
+            Location::Coordinates { lat, lon } => Some((*lat, *lon)),
+            Location::Place(place) => Some((place.lat, place.lon)),
+            _ => None,
+        }
+    }
+
+    /// Timezone of the place or, for coordinates, of the nearest known place. UTC when nothing is known.
+    pub fn timezone(&self, geocoder: Option<&Geocoder>) -> Tz {
+        let tz = match (self, geocoder) {
+            (Location::Place(place), _) => place.tz(),
+            (_, Some(geocoder)) => self
+                .coordinates()
+                .and_then(|(lat, lon)| geocoder.nearest(lat, lon))
+                .and_then(|place| place.tz()),
+            _ => None,
+        };
+
+        tz.unwrap_or(Tz::UTC)
+    }
+
+    fn parse_coordinates(lat: &str, lon: &str) -> Result<Self, LocationError> {
+        let invalid = || LocationError::InvalidCoordinates { lat: lat.to_owned(), lon: lon.to_owned() };
+
+        let lat_value = lat.trim().parse::<f64>().map_err(|_| invalid())?;
+        let lon_value = lon.trim().parse::<f64>().map_err(|_| invalid())?;
+
+        if !lat_value.is_finite() || !lon_value.is_finite() || !(-90.0..=90.0).contains(&lat_value) || !(-180.0..=180.0).contains(&lon_value) {
+            return Err(invalid());
+        }
+
+        Ok(Location::Coordinates { lat: lat_value, lon: lon_value })
+    }
+
+    fn strip_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+        match path.get(..prefix.len()) {
+            Some(head) if head.eq_ignore_ascii_case(prefix) => Some(&path[prefix.len()..]),
+            _ => None,
+        }
+    }
+
+    fn parse_airport(code: &str) -> Result<Self, LocationError> {
+        let code = code.trim();
+
+        match code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+            true => Ok(Location::Airport(code.to_uppercase())),
+            _ => Err(LocationError::InvalidAirportCode { code: code.to_owned() }),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Location::City(name) => write!(f, "{}", name),
+            Location::Coordinates { lat, lon } => write!(f, "{},{}", lat, lon),
+            Location::PostalCode(code) => write!(f, "{}{}", Self::POSTAL_CODE_PREFIX, code),
+            Location::Airport(code) => write!(f, "{}{}", Self::AIRPORT_PREFIX, code),
+            Location::Place(place) => write!(f, "{}, {}", place.name, place.country_code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod location_test {
+    use crate::location::{Location, LocationError};
+    use crate::geocoder::Geocoder;
+
+    #[test]
+    fn test_parse_city() {
+        assert_eq!(Location::parse("Moscow", None, None).unwrap(), Location::City(String::from("Moscow")));
+        assert_eq!(Location::parse("New York", None, None).unwrap(), Location::City(String::from("New York")));
+        assert_eq!(Location::parse("Москва", None, None).unwrap(), Location::City(String::from("Москва")));
+    }
+
+    #[test]
+    fn test_parse_coordinates_from_path() {
+        assert_eq!(Location::parse("55.75,37.61", None, None).unwrap(), Location::Coordinates { lat: 55.75, lon: 37.61 });
+    }
+
+    #[test]
+    fn test_parse_coordinates_from_query_take_precedence() {
+        assert_eq!(Location::parse("here", Some("-33.86"), Some("151.2")).unwrap(), Location::Coordinates { lat: -33.86, lon: 151.2 });
+    }
+
+    #[test]
+    fn test_parse_coordinates_fail_if_out_of_range() {
+        assert!(Location::parse("here", Some("91"), Some("0")).is_err());
+        assert!(Location::parse("here", Some("abc"), Some("0")).is_err());
+    }
+
+    #[test]
+    fn test_parse_coordinates_fail_if_one_is_missing() {
+        assert!(matches!(Location::parse("Moscow", Some("55.75"), None), Err(LocationError::IncompleteCoordinates { ref missing }) if missing == "lon"));
+        assert!(matches!(Location::parse("Moscow", None, Some("37.61")), Err(LocationError::IncompleteCoordinates { ref missing }) if missing == "lat"));
+    }
+
+    #[test]
+    fn test_parse_postal_code() {
+        assert_eq!(Location::parse("zip:SW1A 1AA", None, None).unwrap(), Location::PostalCode(String::from("SW1A 1AA")));
+        assert_eq!(Location::parse("101000", None, None).unwrap(), Location::PostalCode(String::from("101000")));
+    }
+
+    #[test]
+    fn test_parse_airport() {
+        assert_eq!(Location::parse("iata:svo", None, None).unwrap(), Location::Airport(String::from("SVO")));
+        assert!(Location::parse("iata:SVOX", None, None).is_err());
+    }
+
+    fn create_geocoder() -> Geocoder {
+        Geocoder::from_reader(concat!(
+            "524901\tMoscow\tMoscow\tМосква\t55.75222\t37.61556\tP\tPPLC\tRU\t\t48\t\t\t\t10381222\t\t144\tEurope/Moscow\t2019-03-20\n",
+            "4409896\tSpringfield\tSpringfield\t\t37.21533\t-93.29824\tP\tPPLA2\tUS\t\tMO\t077\t\t\t166810\t396\t393\tAmerica/Chicago\t2017-05-23\n",
+            "4250542\tSpringfield\tSpringfield\t\t39.80172\t-89.64371\tP\tPPLA\tUS\t\tIL\t167\t\t\t116565\t182\t184\tAmerica/Chicago\t2017-05-23\n",
+        ).as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_canonicalize_city() {
+        let location = Location::City(String::from("Москва")).canonicalize(&create_geocoder(), None).unwrap();
+        assert_eq!(location.coordinates(), Some((55.75222, 37.61556)));
+        assert_eq!(location.to_string(), "Moscow, RU");
+
+        let unknown = Location::City(String::from("Atlantis")).canonicalize(&create_geocoder(), None).unwrap();
+        assert_eq!(unknown, Location::City(String::from("Atlantis")));
+    }
+
+    #[test]
+    fn test_canonicalize_misspelled_city() {
+        match Location::City(String::from("Mosow")).canonicalize(&create_geocoder(), None) {
+            Err(LocationError::UnknownLocation { candidates, .. }) => assert_eq!(candidates[0].id, 524901),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_ambiguous_city() {
+        match Location::City(String::from("Springfield")).canonicalize(&create_geocoder(), None) {
+            Err(LocationError::AmbiguousLocation { candidates, .. }) => assert_eq!(candidates.len(), 2),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_key() {
+        let geocoder = create_geocoder();
+
+        assert_eq!(Location::from_place_id(&geocoder, 4250542).unwrap().key(), "place:4250542");
+        assert_ne!(Location::from_place_id(&geocoder, 4250542).unwrap().key(), Location::from_place_id(&geocoder, 4409896).unwrap().key());
+        assert_eq!(Location::Coordinates { lat: 37.0, lon: -93.0 }.key(), "37,-93");
+    }
+
+    #[test]
+    fn test_timezone() {
+        let geocoder = create_geocoder();
+
+        assert_eq!(Location::from_place_id(&geocoder, 524901).unwrap().timezone(None), chrono_tz::Europe::Moscow);
+        assert_eq!(Location::Coordinates { lat: 37.0, lon: -93.0 }.timezone(Some(&geocoder)), chrono_tz::America::Chicago);
+        assert_eq!(Location::Coordinates { lat: 37.0, lon: -93.0 }.timezone(None), chrono_tz::UTC);
+        assert_eq!(Location::City(String::from("Atlantis")).timezone(Some(&geocoder)), chrono_tz::UTC);
+    }
+
+    #[test]
+    fn test_from_place_id() {
+        let location = Location::from_place_id(&create_geocoder(), 4250542).unwrap();
+        assert_eq!(location.coordinates(), Some((39.80172, -89.64371)));
+
+        assert!(Location::from_place_id(&create_geocoder(), 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_fail_if_empty() {
+        assert!(Location::parse("  ", None, None).is_err());
+    }
+}
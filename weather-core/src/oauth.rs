@@ -0,0 +1,370 @@
+//! OAuth 1.0a request signing (RFC 5849), used by providers behind OAuth (Yahoo).
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+use crypto::hmac::Hmac;
+use crypto::mac::Mac;
+use crypto::sha1::Sha1;
+use crypto::sha2::Sha256;
+use rand::{thread_rng, Rng};
+use rand::distributions::Alphanumeric;
+use thiserror::Error;
+use url::Url;
+use url::percent_encoding::percent_decode;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum OAuthError {
+    #[error("Invalid url {}", .url)]
+    InvalidUrl {
+        url: String,
+    },
+
+    #[error("Invalid OAuth authorization header")]
+    InvalidHeader {},
+
+    #[error("Unknown consumer key {}", .consumer_key)]
+    UnknownConsumerKey {
+        consumer_key: String,
+    },
+
+    #[error("Unsupported signature method {}", .method)]
+    UnsupportedSignatureMethod {
+        method: String,
+    },
+
+    #[error("Invalid signature")]
+    InvalidSignature {},
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignatureMethod {
+    HmacSha1,
+    HmacSha256,
+    Plaintext,
+}
+
+impl SignatureMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignatureMethod::HmacSha1 => "HMAC-SHA1",
+            SignatureMethod::HmacSha256 => "HMAC-SHA256",
+            SignatureMethod::Plaintext => "PLAINTEXT",
+        }
+    }
+
+    pub fn parse(method: &str) -> Result<Self, OAuthError> {
+        match method {
+            "HMAC-SHA1" => Ok(SignatureMethod::HmacSha1),
+            "HMAC-SHA256" => Ok(SignatureMethod::HmacSha256),
+            "PLAINTEXT" => Ok(SignatureMethod::Plaintext),
+            _ => Err(OAuthError::UnsupportedSignatureMethod { method: method.to_owned() }),
+        }
+    }
+}
+
+/// RFC 3986 percent encoding: everything except unreserved characters (`A-Z a-z 0-9 - . _ ~`), UTF-8 bytes, upper case hex.
+pub fn percent_encode(value: &str) -> String {
+    value.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Parameters of an `Authorization: OAuth ...` header, decoded, `realm` included.
+pub fn parse_authorization_header(header: &str) -> Result<BTreeMap<String, String>, OAuthError> {
+    let params = header.trim().strip_prefix("OAuth ").ok_or(OAuthError::InvalidHeader {})?;
+
+    params.split(',')
+        .map(|pair| {
+            let (name, value) = pair.trim().split_once('=').ok_or(OAuthError::InvalidHeader {})?;
+            let value = value.trim_matches('"');
+
+            Ok((name.to_owned(), percent_decode(value.as_bytes()).decode_utf8_lossy().into_owned()))
+        })
+        .collect()
+}
+
+/// Signs requests with consumer (and optionally token) credentials.
+///
+/// Nonce and timestamp are generated for every request unless fixed with `with_nonce`/`with_timestamp`
+/// (tests, signature checks).
+#[derive(Debug, Clone)]
+pub struct OAuthSigner {
+    consumer_key: String,
+    consumer_secret: String,
+    token: Option<(String, String)>,
+    method: SignatureMethod,
+    version: bool,
+    nonce: Option<String>,
+    timestamp: Option<u64>,
+}
+
+impl OAuthSigner {
+    pub fn new(consumer_key: String, consumer_secret: String) -> Self {
+        OAuthSigner {
+            consumer_key,
+            consumer_secret,
+            token: None,
+            method: SignatureMethod::HmacSha1,
+            version: false,
+            nonce: None,
+            timestamp: None,
+        }
+    }
+
+    pub fn with_token(mut self, token: String, token_secret: String) -> Self {
+        self.token = Some((token, token_secret));
+        self
+    }
+
+    pub fn with_signature_method(mut self, method: SignatureMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Sends the optional `oauth_version=1.0`, some servers (Yahoo) want it.
+    pub fn with_version(mut self) -> Self {
+        self.version = true;
+        self
+    }
+
+    pub fn with_nonce(mut self, nonce: String) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Protocol parameters of the next request, without the signature.
+    pub fn oauth_params(&self) -> BTreeMap<String, String> {
+        let mut params = BTreeMap::new();
+
+        params.insert("oauth_consumer_key".to_owned(), self.consumer_key.clone());
+        params.insert("oauth_nonce".to_owned(), self.nonce.clone().unwrap_or_else(|| thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .collect::<String>()));
+        params.insert("oauth_signature_method".to_owned(), self.method.as_str().to_owned());
+        params.insert("oauth_timestamp".to_owned(), self.timestamp.unwrap_or_else(|| SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or(Duration::from_secs(0))
+            .as_secs()).to_string());
+
+        if let Some((ref token, _)) = self.token {
+            params.insert("oauth_token".to_owned(), token.clone());
+        }
+        if self.version {
+            params.insert("oauth_version".to_owned(), "1.0".to_owned());
+        }
+
+        params
+    }
+
+    /// `Authorization` header value for a request to `url` (query parameters are signed too),
+    /// `form_params` are the `application/x-www-form-urlencoded` body parameters, if any.
+    pub fn authorization_header(&self, method: &str, url: &str, form_params: &[(String, String)]) -> Result<String, OAuthError> {
+        let mut params = self.oauth_params();
+        let signature = self.sign(method, url, form_params, &params)?;
+        params.insert("oauth_signature".to_owned(), signature);
+
+        Ok(format!(
+            "OAuth {}",
+            params.iter()
+                .map(|(name, value)| format!("{}=\"{}\"", percent_encode(name), percent_encode(value)))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ))
+    }
+
+    /// Signature over the signature base string (RFC 5849, 3.4).
+    pub fn sign(&self, method: &str, url: &str, form_params: &[(String, String)], oauth_params: &BTreeMap<String, String>) -> Result<String, OAuthError> {
+        let key = format!(
+            "{}&{}",
+            percent_encode(&self.consumer_secret),
+            percent_encode(self.token.as_ref().map(|(_, secret)| secret.as_str()).unwrap_or(""))
+        );
+
+        let signature = match self.method {
+            SignatureMethod::HmacSha1 => Self::hmac(Hmac::new(Sha1::new(), key.as_bytes()), &base_string(method, url, form_params, oauth_params)?),
+            SignatureMethod::HmacSha256 => Self::hmac(Hmac::new(Sha256::new(), key.as_bytes()), &base_string(method, url, form_params, oauth_params)?),
+            SignatureMethod::Plaintext => key,
+        };
+
+        Ok(signature)
+    }
+
+    /// Checks a signed request the way the server would: consumer key, signature method and signature,
+    /// nonce and timestamp are taken from the header as is.
+    pub fn verify(&self, method: &str, url: &str, form_params: &[(String, String)], authorization: &str) -> Result<(), OAuthError> {
+        let mut params = parse_authorization_header(authorization)?;
+        let signature = params.remove("oauth_signature").ok_or(OAuthError::InvalidHeader {})?;
+
+        let consumer_key = params.get("oauth_consumer_key").cloned().unwrap_or_default();
+        if consumer_key != self.consumer_key {
+            return Err(OAuthError::UnknownConsumerKey { consumer_key });
+        }
+
+        let signer = self.clone().with_signature_method(
+            SignatureMethod::parse(params.get("oauth_signature_method").map(|method| method.as_str()).unwrap_or(""))?
+        );
+
+        match signer.sign(method, url, form_params, &params)? == signature {
+            true => Ok(()),
+            false => Err(OAuthError::InvalidSignature {}),
+        }
+    }
+
+    fn hmac<D: crypto::digest::Digest>(mut mac: Hmac<D>, base_string: &str) -> String {
+        mac.input(base_string.as_bytes());
+
+        base64::encode(mac.result().code())
+    }
+}
+
+/// `METHOD&base-uri&normalized-params` (RFC 5849, 3.4.1): url without query, default port and with lower case
+/// scheme and host; query, body and protocol parameters (except `realm` and `oauth_signature`) encoded and sorted.
+pub fn base_string(method: &str, url: &str, form_params: &[(String, String)], oauth_params: &BTreeMap<String, String>) -> Result<String, OAuthError> {
+    let parsed = Url::parse(url).map_err(|_| OAuthError::InvalidUrl { url: url.to_owned() })?;
+    let host = parsed.host_str().ok_or_else(|| OAuthError::InvalidUrl { url: url.to_owned() })?;
+
+    let base_uri = match parsed.port() {
+        Some(port) => format!("{}://{}:{}{}", parsed.scheme(), host, port, parsed.path()),
+        None => format!("{}://{}{}", parsed.scheme(), host, parsed.path()),
+    };
+
+    let mut params: Vec<(String, String)> = parsed.query_pairs()
+        .map(|(name, value)| (percent_encode(&name), percent_encode(&value)))
+        .chain(form_params.iter().map(|(name, value)| (percent_encode(name), percent_encode(value))))
+        .chain(oauth_params.iter()
+            .filter(|(name, _)| name.as_str() != "realm" && name.as_str() != "oauth_signature")
+            .map(|(name, value)| (percent_encode(name), percent_encode(value))))
+        .collect();
+    params.sort();
+
+    let normalized = params.iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<String>>()
+        .join("&");
+
+    Ok(format!("{}&{}&{}", method.to_uppercase(), percent_encode(&base_uri), percent_encode(&normalized)))
+}
+
+#[cfg(test)]
+mod oauth_test {
+    use std::collections::BTreeMap;
+    use crate::oauth::*;
+
+    /// RFC 5849, 1.2: photos.example.net credentials.
+    fn photos_signer() -> OAuthSigner {
+        OAuthSigner::new(String::from("dpf43f3p2l4k3l03"), String::from("kd94hf93k423kf44"))
+            .with_token(String::from("nnch734d00sl2jdk"), String::from("pfkkdhi9sl3r4s00"))
+            .with_nonce(String::from("chapoH"))
+            .with_timestamp(137131202)
+    }
+
+    const PHOTOS_URL: &str = "http://photos.example.net/photos?file=vacation.jpg&size=original";
+
+    #[test]
+    fn test_percent_encode_unreserved_only() {
+        assert_eq!(percent_encode("Ladies + Gentlemen"), "Ladies%20%2B%20Gentlemen");
+        assert_eq!(percent_encode("An encoded string!"), "An%20encoded%20string%21");
+        assert_eq!(percent_encode("Dogs, Cats & Mice"), "Dogs%2C%20Cats%20%26%20Mice");
+        assert_eq!(percent_encode("-._~"), "-._~");
+        assert_eq!(percent_encode("Москва"), "%D0%9C%D0%BE%D1%81%D0%BA%D0%B2%D0%B0");
+    }
+
+    #[test]
+    fn test_rfc5849_base_string_example() {
+        // RFC 5849, 3.4.1.1
+        let oauth_params: BTreeMap<String, String> = vec![
+            ("realm", "Example"),
+            ("oauth_consumer_key", "9djdj82h48djs9d2"),
+            ("oauth_token", "kkk9d7dh3k39sjv7"),
+            ("oauth_signature_method", "HMAC-SHA1"),
+            ("oauth_timestamp", "137131201"),
+            ("oauth_nonce", "7d8f3e4a"),
+        ].into_iter().map(|(name, value)| (name.to_owned(), value.to_owned())).collect();
+        let form_params = vec![(String::from("c2"), String::new()), (String::from("a3"), String::from("2 q"))];
+
+        assert_eq!(
+            base_string("POST", "http://example.com/request?b5=%3D%253D&a3=a&c%40=&a2=r%20b", &form_params, &oauth_params).unwrap(),
+            "POST&http%3A%2F%2Fexample.com%2Frequest&a2%3Dr%2520b%26a3%3D2%2520q%26a3%3Da%26b5%3D%253D%25253D%26c%2540%3D\
+             %26c2%3D%26oauth_consumer_key%3D9djdj82h48djs9d2%26oauth_nonce%3D7d8f3e4a%26oauth_signature_method%3DHMAC-SHA1\
+             %26oauth_timestamp%3D137131201%26oauth_token%3Dkkk9d7dh3k39sjv7"
+        );
+    }
+
+    #[test]
+    fn test_rfc5849_base_string_uri() {
+        // RFC 5849, 3.4.1.2
+        let empty = BTreeMap::new();
+
+        assert!(base_string("GET", "HTTP://EXAMPLE.COM:80/r%20v/X?id=123", &[], &empty).unwrap()
+            .starts_with("GET&http%3A%2F%2Fexample.com%2Fr%2520v%2FX&"));
+        assert!(base_string("GET", "https://www.example.net:8080/?q=1", &[], &empty).unwrap()
+            .starts_with("GET&https%3A%2F%2Fwww.example.net%3A8080%2F&"));
+    }
+
+    #[test]
+    fn test_rfc5849_hmac_sha1_example() {
+        let header = photos_signer().authorization_header("GET", PHOTOS_URL, &[]).unwrap();
+
+        assert_eq!(
+            header,
+            "OAuth oauth_consumer_key=\"dpf43f3p2l4k3l03\", oauth_nonce=\"chapoH\", oauth_signature=\"MdpQcU8iPSUjWoN%2FUDMsK2sui9I%3D\", \
+             oauth_signature_method=\"HMAC-SHA1\", oauth_timestamp=\"137131202\", oauth_token=\"nnch734d00sl2jdk\""
+        );
+    }
+
+    #[test]
+    fn test_hmac_sha256_signature() {
+        let signer = photos_signer().with_signature_method(SignatureMethod::HmacSha256);
+
+        assert_eq!(
+            signer.sign("GET", PHOTOS_URL, &[], &signer.oauth_params()).unwrap(),
+            "HtMwoX2zenlFjgGg/SNEoKEQmL7CzxYFEKzs7er044Y="
+        );
+    }
+
+    #[test]
+    fn test_rfc5849_plaintext_example() {
+        // RFC 5849, 1.2: temporary credentials request, no token yet, then token credentials request.
+        let signer = OAuthSigner::new(String::from("dpf43f3p2l4k3l03"), String::from("kd94hf93k423kf44"))
+            .with_signature_method(SignatureMethod::Plaintext);
+        assert_eq!(signer.sign("POST", "https://photos.example.net/initiate", &[], &signer.oauth_params()).unwrap(), "kd94hf93k423kf44&");
+
+        let signer = signer.with_token(String::from("hh5s93j4hdidpola"), String::from("hdhd0244k9j7ao03"));
+        assert_eq!(signer.sign("POST", "https://photos.example.net/token", &[], &signer.oauth_params()).unwrap(), "kd94hf93k423kf44&hdhd0244k9j7ao03");
+    }
+
+    #[test]
+    fn test_verify_signed_request() {
+        let signer = OAuthSigner::new(String::from("key"), String::from("secret")).with_version();
+        let url = "https://weather-ydn-yql.media.yahoo.com/forecastrss?format=json&location=%D0%9C%D0%BE%D1%81%D0%BA%D0%B2%D0%B0&u=c";
+        let header = signer.authorization_header("GET", url, &[]).unwrap();
+
+        assert_eq!(signer.verify("GET", url, &[], &header), Ok(()));
+        assert_eq!(
+            OAuthSigner::new(String::from("key"), String::from("other")).verify("GET", url, &[], &header),
+            Err(OAuthError::InvalidSignature {})
+        );
+        assert_eq!(
+            OAuthSigner::new(String::from("other"), String::from("secret")).verify("GET", url, &[], &header),
+            Err(OAuthError::UnknownConsumerKey { consumer_key: String::from("key") })
+        );
+        assert_eq!(signer.verify("GET", &url.replace("u=c", "u=f"), &[], &header), Err(OAuthError::InvalidSignature {}));
+    }
+
+    #[test]
+    fn test_parse_authorization_header() {
+        let params = parse_authorization_header("OAuth realm=\"Photos\", oauth_consumer_key=\"dpf43f3p2l4k3l03\", oauth_signature=\"MdpQcU8iPSUjWoN%2FUDMsK2sui9I%3D\"").unwrap();
+
+        assert_eq!(params["realm"], "Photos");
+        assert_eq!(params["oauth_signature"], "MdpQcU8iPSUjWoN/UDMsK2sui9I=");
+        assert_eq!(parse_authorization_header("Basic dXNlcg=="), Err(OAuthError::InvalidHeader {}));
+    }
+}
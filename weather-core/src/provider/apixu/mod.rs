@@ -0,0 +1,171 @@
+use async_trait::async_trait;
+use crate::provider::{WeatherProvider, ProviderError};
+use crate::provider::{WeatherProviderResponse, WeatherProviderCurrentResponse};
+use crate::current::CurrentConditions;
+use super::utils;
+use std::sync::Arc;
+use crate::transport::{HttpTransport, HttpRequest};
+use crate::RawForecast;
+use crate::forecast::Forecast;
+use crate::location::{Location, LocationType};
+use crate::provider::capabilities::{ProviderCapabilities, RateLimit, Variable};
+use url::form_urlencoded;
+
+mod test;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApixuResponse {
+    forecast: ApixuForecast,
+}
+
+impl WeatherProviderResponse for ApixuResponse {
+    fn to_forecast(&self) -> Forecast {
+        Forecast::new(
+            self.forecast.forecastday
+                .iter()
+                .map(|fd| (fd.date.clone(), fd.day.avgtemp_c))
+                .collect::<RawForecast>(),
+            String::from(ApixuProvider::NAME)
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApixuForecast {
+    forecastday: Vec<ApixuForecastDay>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApixuForecastDay {
+    date: String,
+    day: ApixuDay,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApixuDay {
+    avgtemp_c: f64,
+    avgtemp_f: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApixuCurrentResponse {
+    current: ApixuCurrent,
+}
+
+impl WeatherProviderCurrentResponse for ApixuCurrentResponse {
+    fn to_current_conditions(&self) -> CurrentConditions {
+        CurrentConditions {
+            provider: String::from(ApixuProvider::NAME),
+            observed_at: self.current.last_updated_epoch,
+            temperature: self.current.temp_c,
+            humidity: self.current.humidity,
+            wind_kph: self.current.wind_kph,
+            condition: self.current.condition.as_ref().map(|condition| condition.text.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApixuCurrent {
+    last_updated_epoch: i64,
+    temp_c: f64,
+    humidity: Option<f64>,
+    wind_kph: Option<f64>,
+    condition: Option<ApixuCondition>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApixuCondition {
+    text: String,
+}
+
+pub struct ApixuProvider {
+    transport: Arc<dyn HttpTransport>,
+    key: String,
+    base_uri: String,
+}
+
+impl ApixuProvider {
+    pub const BASE_URI: &'static str = "http://api.apixu.com/v1";
+    const NAME: &'static str = "Apixu";
+    const HORIZON_DAYS: u32 = 7;
+
+    pub fn new(transport: Arc<dyn HttpTransport>, key: String) -> Self {
+        ApixuProvider {transport, key, base_uri: Self::BASE_URI.to_owned()}
+    }
+
+    /// Sends requests to another Apixu compatible API (a fake upstream in tests), `base_uri` without trailing slash.
+    pub fn with_base_uri(mut self, base_uri: String) -> Self {
+        self.base_uri = base_uri;
+        self
+    }
+
+    fn request_error(e: utils::RequestError) -> ProviderError {
+        e.into_provider_error(Self::NAME)
+    }
+
+    fn build_forecast_url(&self, location: &Location) -> String {
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("key", &self.key)
+            .append_pair("q", &Self::location_query(location))
+            .append_pair("days", &Self::HORIZON_DAYS.to_string())
+            .finish();
+
+        format!("{}/forecast.json?{}", self.base_uri, query)
+    }
+
+    fn build_current_url(&self, location: &Location) -> String {
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("key", &self.key)
+            .append_pair("q", &Self::location_query(location))
+            .finish();
+
+        format!("{}/current.json?{}", self.base_uri, query)
+    }
+
+    fn location_query(location: &Location) -> String {
+        if let Some((lat, lon)) = location.coordinates() {
+            return format!("{},{}", lat, lon);
+        }
+
+        match location {
+            Location::City(name) => name.clone(),
+            Location::PostalCode(code) => code.clone(),
+            Location::Airport(code) => format!("iata:{}", code),
+            _ => location.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for ApixuProvider {
+    /// Free plan limits, forecasts are asked for `HORIZON_DAYS` days.
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            name: Self::NAME.to_owned(),
+            horizon_days: Self::HORIZON_DAYS,
+            hourly: true,
+            variables: vec![Variable::Temperature, Variable::Humidity, Variable::Wind, Variable::Condition],
+            location_types: vec![LocationType::City, LocationType::Coordinates, LocationType::PostalCode, LocationType::Airport, LocationType::Place],
+            rate_limit: Some(RateLimit { requests: 10_000, period_seconds: 30 * 24 * 3600 }),
+        }
+    }
+
+    async fn get_forecast(&self, location: &Location) -> Result<Forecast, ProviderError> {
+        let forecast = utils::fetch_forecast_request::<ApixuResponse>(self.transport.as_ref(), HttpRequest::get(self.build_forecast_url(location)))
+            .await
+            .map_err(Self::request_error)?;
+
+        tracing::debug!(provider = Self::NAME, location = %location, days = forecast.len(), "forecast received");
+        Ok(forecast)
+    }
+
+    async fn get_current_conditions(&self, location: &Location) -> Result<CurrentConditions, ProviderError> {
+        let current = utils::fetch_current_conditions_request::<ApixuCurrentResponse>(self.transport.as_ref(), HttpRequest::get(self.build_current_url(location)))
+            .await
+            .map_err(Self::request_error)?;
+
+        tracing::debug!(provider = Self::NAME, location = %location, observed_at = current.observed_at, "current conditions received");
+        Ok(current)
+    }
+}
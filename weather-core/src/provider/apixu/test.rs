@@ -0,0 +1,182 @@
+#![cfg(test)]
+use super::*;
+use crate::transport::TransportError;
+use crate::transport::mock::{MockTransport, MockResponse};
+use crate::transport::cassette::CassetteTransport;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const FORECAST_RESPONSE: &str = r#"{
+    "location": {"name": "Moscow", "tz_id": "Europe/Moscow"},
+    "forecast": {
+        "forecastday": [
+            {"date": "2019-03-26", "day": {"avgtemp_c": 1.5, "avgtemp_f": 34.7}},
+            {"date": "2019-03-27", "day": {"avgtemp_c": 2.5, "avgtemp_f": 36.5}}
+        ]
+    }
+}"#;
+
+const CURRENT_RESPONSE: &str = r#"{
+    "location": {"name": "Moscow"},
+    "current": {"last_updated_epoch": 1553600700, "temp_c": 5.0, "wind_kph": 11.2, "humidity": 70}
+}"#;
+
+const ERROR_RESPONSE: &str = r#"{"error": {"code": 1006, "message": "No matching location found."}}"#;
+
+fn create_apixu_provider(transport: MockTransport) -> (ApixuProvider, Arc<MockTransport>) {
+    let transport = Arc::new(transport);
+
+    (ApixuProvider::new(transport.clone(), String::from("key")), transport)
+}
+
+#[actix_rt::test]
+async fn test_apixu_return_forecast() {
+    let (provider, transport) = create_apixu_provider(MockTransport::new().on(&["forecast.json"], MockResponse::json(FORECAST_RESPONSE)));
+
+    let forecast_opt = provider.get_forecast(&Location::City(String::from("Moscow"))).await;
+    assert!(forecast_opt.is_ok());
+
+    let forecast = forecast_opt.unwrap();
+    assert_eq!(forecast.get_temperature_at(String::from("2019-03-27")), Some(&2.5));
+    assert_eq!(forecast.get_source_name(), "Apixu");
+
+    assert_eq!(transport.requests()[0].url, "http://api.apixu.com/v1/forecast.json?key=key&q=Moscow&days=7");
+}
+
+#[actix_rt::test]
+async fn test_apixu_error_for_invalid_location() {
+    let (provider, _) = create_apixu_provider(MockTransport::new().on(&["forecast.json"], MockResponse::status(400, ERROR_RESPONSE)));
+
+    match provider.get_forecast(&Location::City(String::from("UnknownCityInUnknownCountry"))).await {
+        Err(e @ ProviderError::RequestError { retryable: false, .. }) => assert!(!e.is_provider_failure()),
+        _ => panic!("Request error expected"),
+    }
+}
+
+#[actix_rt::test]
+async fn test_apixu_error_for_rejected_key() {
+    let (provider, _) = create_apixu_provider(
+        MockTransport::new().on(&["forecast.json"], MockResponse::status(403, r#"{"error": {"code": 2008, "message": "API key has been disabled."}}"#))
+    );
+
+    match provider.get_forecast(&Location::City(String::from("Moscow"))).await {
+        Err(e @ ProviderError::Unauthorized { .. }) => assert!(e.is_provider_failure()),
+        _ => panic!("Unauthorized error expected"),
+    }
+}
+
+#[actix_rt::test]
+async fn test_apixu_retryable_error_on_server_error() {
+    let (provider, _) = create_apixu_provider(MockTransport::new().on(&["forecast.json"], MockResponse::status(502, "Bad Gateway")));
+
+    match provider.get_forecast(&Location::City(String::from("Moscow"))).await {
+        Err(e @ ProviderError::RequestError { retryable: true, .. }) => assert!(e.is_provider_failure()),
+        _ => panic!("Retryable request error expected"),
+    }
+}
+
+#[actix_rt::test]
+async fn test_apixu_error_on_timeout() {
+    let (provider, _) = create_apixu_provider(
+        MockTransport::new().on(&["forecast.json"], MockResponse::json(FORECAST_RESPONSE).delayed(Duration::from_secs(60)))
+    );
+
+    match provider.get_forecast(&Location::City(String::from("Moscow"))).await {
+        Err(ProviderError::RequestError { retryable, .. }) => assert!(retryable),
+        _ => panic!("Request error expected"),
+    }
+}
+
+#[actix_rt::test]
+async fn test_apixu_error_on_connection_failure() {
+    let (provider, _) = create_apixu_provider(
+        MockTransport::new().on(&["forecast.json"], MockResponse::error(TransportError::Connection { reason: String::from("Connection refused") }))
+    );
+
+    assert!(provider.get_forecast(&Location::City(String::from("Moscow"))).await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_apixu_return_current_conditions() {
+    let (provider, transport) = create_apixu_provider(MockTransport::new().on(&["current.json"], MockResponse::json(CURRENT_RESPONSE)));
+
+    let current_opt = provider.get_current_conditions(&Location::City(String::from("Moscow"))).await;
+    assert!(current_opt.is_ok());
+    assert_eq!(current_opt.unwrap().observed_at, 1553600700);
+
+    assert_eq!(transport.requests()[0].url, "http://api.apixu.com/v1/current.json?key=key&q=Moscow");
+}
+
+fn cassette_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/cassettes/apixu.json")
+}
+
+#[actix_rt::test]
+async fn test_apixu_replay_recorded_responses() {
+    let provider = ApixuProvider::new(Arc::new(CassetteTransport::replay(&cassette_path()).unwrap()), String::from("key"));
+    let location = Location::City(String::from("Moscow"));
+
+    assert!(provider.get_forecast(&location).await.is_ok());
+    assert!(provider.get_current_conditions(&location).await.is_ok());
+}
+
+/// Refreshes fixtures/cassettes/apixu.json from the live API:
+/// `APIXU_KEY=... cargo test -p weather_core record_apixu_cassette -- --ignored`
+#[cfg(feature = "reqwest-transport")]
+#[actix_rt::test]
+#[ignore]
+async fn record_apixu_cassette() {
+    let transport = CassetteTransport::record(&cassette_path(), Arc::new(crate::transport::ReqwestTransport::default()));
+    let provider = ApixuProvider::new(Arc::new(transport), std::env::var("APIXU_KEY").unwrap());
+    let location = Location::City(String::from("Moscow"));
+
+    provider.get_forecast(&location).await.unwrap();
+    provider.get_current_conditions(&location).await.unwrap();
+}
+
+#[actix_rt::test]
+async fn test_apixu_use_base_uri_override() {
+    let (provider, transport) = create_apixu_provider(MockTransport::new().on(&["forecast.json"], MockResponse::json(FORECAST_RESPONSE)));
+    let provider = provider.with_base_uri(String::from("http://127.0.0.1:8089/v1"));
+
+    assert!(provider.get_forecast(&Location::City(String::from("Moscow"))).await.is_ok());
+    assert_eq!(transport.requests()[0].url, "http://127.0.0.1:8089/v1/forecast.json?key=key&q=Moscow&days=7");
+}
+
+#[test]
+fn test_apixu_current_response_to_current_conditions() {
+    let response: ApixuCurrentResponse = serde_json::from_str(r#"{
+        "location": {"name": "Moscow"},
+        "current": {
+            "last_updated_epoch": 1553600700,
+            "temp_c": 5.0,
+            "wind_kph": 11.2,
+            "humidity": 70,
+            "condition": {"text": "Partly cloudy", "code": 1003}
+        }
+    }"#).unwrap();
+
+    let current = response.to_current_conditions();
+    assert_eq!(current.observed_at, 1553600700);
+    assert_eq!(current.temperature, 5.0);
+    assert_eq!(current.humidity, Some(70.0));
+    assert_eq!(current.condition, Some(String::from("Partly cloudy")));
+}
+
+#[test]
+fn test_apixu_url_encode_location() {
+    let provider = ApixuProvider::new(Arc::new(MockTransport::new()), String::from("key"));
+
+    assert_eq!(
+        provider.build_forecast_url(&Location::City(String::from("New York"))),
+        "http://api.apixu.com/v1/forecast.json?key=key&q=New+York&days=7"
+    );
+    assert_eq!(
+        provider.build_forecast_url(&Location::Coordinates { lat: 55.75, lon: 37.61 }),
+        "http://api.apixu.com/v1/forecast.json?key=key&q=55.75%2C37.61&days=7"
+    );
+    assert_eq!(
+        provider.build_forecast_url(&Location::Airport(String::from("SVO"))),
+        "http://api.apixu.com/v1/forecast.json?key=key&q=iata%3ASVO&days=7"
+    );
+}
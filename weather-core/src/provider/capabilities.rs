@@ -0,0 +1,152 @@
+use chrono::NaiveDate;
+use crate::date;
+use crate::date::Period;
+use crate::forecast::ForecastError;
+use crate::location::{Location, LocationType};
+use crate::provider::ProviderError;
+use crate::warning::Warning;
+
+/// Weather variables a provider reports.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Variable {
+    Temperature,
+    Humidity,
+    Wind,
+    Condition,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RateLimit {
+    pub requests: u32,
+    pub period_seconds: u32,
+}
+
+/// What a provider is able to answer, queries outside of it are not sent to the provider at all.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProviderCapabilities {
+    pub name: String,
+    /// Forecast length in days, today included.
+    pub horizon_days: u32,
+    pub hourly: bool,
+    pub variables: Vec<Variable>,
+    pub location_types: Vec<LocationType>,
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl ProviderCapabilities {
+    pub fn accepts(&self, location: &Location) -> bool {
+        self.location_types.contains(&location.location_type())
+    }
+
+    /// Some date of the period is within the forecast horizon, from `today` on,
+    /// dates out of the horizon are reported by the forecast filters.
+    pub fn overlaps(&self, period: &Period, today: &NaiveDate) -> bool {
+        let (first, last) = period.bounds();
+
+        last >= *today && first <= date::last_day(today, self.horizon_days)
+    }
+
+    pub fn can_forecast(&self, location: &Location, period: &Period, today: &NaiveDate) -> bool {
+        self.skip_warning(location, period, today).is_none()
+    }
+
+    /// Why the provider is not asked for the period at `location`, `None` if it is.
+    pub fn skip_warning(&self, location: &Location, period: &Period, today: &NaiveDate) -> Option<Warning> {
+        if !self.accepts(location) {
+            return Some(ProviderError::UnsupportedLocation {
+                location: location.to_string(),
+                provider_name: self.name.clone(),
+            }.into());
+        }
+
+        let (first, last) = period.bounds();
+
+        if last < *today {
+            return Some(ForecastError::PastPeriod {
+                to: date::format_date(&last),
+                first_day: date::format_date(today),
+                provider_name: self.name.clone(),
+            }.into());
+        }
+
+        if !self.overlaps(period, today) {
+            return Some(ForecastError::BeyondHorizon {
+                from: date::format_date(&first),
+                last_day: date::format_date(&date::last_day(today, self.horizon_days)),
+                provider_name: self.name.clone(),
+            }.into());
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod capabilities_test {
+    use chrono::NaiveDate;
+    use crate::date::Period;
+    use crate::location::{Location, LocationType};
+    use crate::provider::capabilities::{ProviderCapabilities, Variable};
+    use crate::warning::WarningCode;
+
+    fn create_capabilities() -> ProviderCapabilities {
+        ProviderCapabilities {
+            name: String::from("provider_stub"),
+            horizon_days: 7,
+            hourly: false,
+            variables: vec![Variable::Temperature],
+            location_types: vec![LocationType::City, LocationType::Coordinates],
+            rate_limit: None,
+        }
+    }
+
+    #[test]
+    fn test_accepts_listed_location_types_only() {
+        let capabilities = create_capabilities();
+
+        assert!(capabilities.accepts(&Location::City(String::from("Moscow"))));
+        assert!(capabilities.accepts(&Location::Coordinates { lat: 55.75, lon: 37.61 }));
+        assert!(!capabilities.accepts(&Location::Airport(String::from("SVO"))));
+    }
+
+    #[test]
+    fn test_overlaps_periods_within_horizon() {
+        let capabilities = create_capabilities();
+        let today = NaiveDate::from_ymd_opt(2019, 3, 1).unwrap();
+
+        assert!(capabilities.overlaps(&Period::Date(NaiveDate::from_ymd_opt(2019, 3, 7).unwrap()), &today));
+        assert!(!capabilities.overlaps(&Period::Date(NaiveDate::from_ymd_opt(2019, 3, 8).unwrap()), &today));
+        assert!(capabilities.overlaps(&Period::Week { today, days: 7 }, &today));
+        assert!(capabilities.overlaps(&Period::Range { from: NaiveDate::from_ymd_opt(2019, 3, 5).unwrap(), to: NaiveDate::from_ymd_opt(2019, 3, 10).unwrap() }, &today));
+        assert!(!capabilities.overlaps(&Period::Range { from: NaiveDate::from_ymd_opt(2019, 3, 8).unwrap(), to: NaiveDate::from_ymd_opt(2019, 3, 10).unwrap() }, &today));
+        assert!(capabilities.overlaps(&Period::Range { from: NaiveDate::from_ymd_opt(2019, 2, 25).unwrap(), to: today }, &today));
+        assert!(!capabilities.overlaps(&Period::Range { from: NaiveDate::from_ymd_opt(2019, 2, 20).unwrap(), to: NaiveDate::from_ymd_opt(2019, 2, 28).unwrap() }, &today));
+        assert!(!capabilities.overlaps(&Period::Date(NaiveDate::from_ymd_opt(2019, 2, 28).unwrap()), &today));
+    }
+
+    #[test]
+    fn test_skip_warning() {
+        let capabilities = create_capabilities();
+        let today = NaiveDate::from_ymd_opt(2019, 3, 1).unwrap();
+        let city = Location::City(String::from("Moscow"));
+
+        assert_eq!(capabilities.skip_warning(&city, &Period::Week { today, days: 7 }, &today), None);
+
+        let warning = capabilities.skip_warning(&city, &Period::Date(NaiveDate::from_ymd_opt(2019, 3, 8).unwrap()), &today).unwrap();
+        assert_eq!(warning.code, WarningCode::BeyondHorizon);
+        assert_eq!(warning.date, Some(String::from("2019-03-08")));
+        assert_eq!(warning.provider, Some(String::from("provider_stub")));
+
+        let warning = capabilities.skip_warning(&city, &Period::Range { from: NaiveDate::from_ymd_opt(2019, 2, 20).unwrap(), to: NaiveDate::from_ymd_opt(2019, 2, 28).unwrap() }, &today).unwrap();
+        assert_eq!(warning.code, WarningCode::BeyondHorizon);
+        assert_eq!(warning.date, Some(String::from("2019-02-28")));
+        assert!(warning.message.contains("in the past"));
+
+        let warning = capabilities.skip_warning(&Location::Airport(String::from("SVO")), &Period::Date(today), &today).unwrap();
+        assert_eq!(warning.code, WarningCode::UnsupportedLocation);
+    }
+}
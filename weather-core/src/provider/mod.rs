@@ -0,0 +1,80 @@
+use crate::Date;
+use crate::forecast::Forecast;
+use crate::location::Location;
+use crate::current::CurrentConditions;
+use self::capabilities::ProviderCapabilities;
+use async_trait::async_trait;
+use thiserror::Error;
+
+pub mod apixu;
+pub mod yahoo;
+pub mod utils;
+pub mod capabilities;
+
+#[derive(Debug, Error)]
+pub enum ProviderError {
+    #[error("Reason:{}, provider: {}!", .reason, .provider_name)]
+    RequestError {
+        reason: String,
+        provider_name: String,
+        retryable: bool,
+    },
+
+    /// Credentials rejected (invalid or expired key), retrying won't help until the configuration is fixed.
+    #[error("Reason:{}, provider: {}!", .reason, .provider_name)]
+    Unauthorized {
+        reason: String,
+        provider_name: String,
+    },
+
+    #[error("Unsupported date {} for provider {} !", .date, .provider_name)]
+    UnsupportedDate {
+        date: Date,
+        provider_name: String,
+    },
+
+    #[error("Unsupported location {} for provider {} !", .location, .provider_name)]
+    UnsupportedLocation {
+        location: String,
+        provider_name: String,
+    },
+}
+
+impl ProviderError {
+    /// Error kind without the details, e.g. for metric labels.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProviderError::RequestError { .. } => "request_error",
+            ProviderError::Unauthorized { .. } => "unauthorized",
+            ProviderError::UnsupportedDate { .. } => "unsupported_date",
+            ProviderError::UnsupportedLocation { .. } => "unsupported_location",
+        }
+    }
+
+    /// The provider itself is failing (down, overloaded, credentials rejected), as opposed to being asked
+    /// for something it doesn't know.
+    pub fn is_provider_failure(&self) -> bool {
+        match self {
+            ProviderError::RequestError { retryable, .. } => *retryable,
+            ProviderError::Unauthorized { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+pub trait WeatherProviderResponse {
+    fn to_forecast(&self) -> Forecast;
+}
+
+pub trait WeatherProviderCurrentResponse {
+    fn to_current_conditions(&self) -> CurrentConditions;
+}
+
+#[async_trait]
+pub trait WeatherProvider: Send + Sync {
+    fn capabilities(&self) -> ProviderCapabilities;
+
+    async fn get_forecast(&self, location: &Location) -> Result<Forecast, ProviderError>;
+
+    async fn get_current_conditions(&self, location: &Location) -> Result<CurrentConditions, ProviderError>;
+}
@@ -0,0 +1,82 @@
+use crate::transport::{HttpTransport, HttpRequest, TransportError};
+use crate::provider::{ProviderError, WeatherProviderResponse, WeatherProviderCurrentResponse};
+use crate::current::CurrentConditions;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use crate::forecast::Forecast;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RequestError {
+    #[error("Request Unknown error")]
+    UnknownError {},
+
+    #[error("Request time out")]
+    ProviderTimeOut {},
+
+    #[error("Invalid response (invalid location)")]
+    InvalidResponse {},
+
+    #[error("Request unauthorized, status {}", .status)]
+    Unauthorized {
+        status: u16,
+    },
+
+    #[error("Provider server error, status {}", .status)]
+    ServerError {
+        status: u16,
+    },
+}
+
+impl RequestError {
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, RequestError::InvalidResponse {} | RequestError::Unauthorized { .. })
+    }
+
+    pub fn into_provider_error(self, provider_name: &str) -> ProviderError {
+        match self {
+            RequestError::Unauthorized { .. } => ProviderError::Unauthorized {
+                reason: self.to_string(),
+                provider_name: provider_name.to_owned(),
+            },
+            _ => ProviderError::RequestError {
+                reason: self.to_string(),
+                provider_name: provider_name.to_owned(),
+                retryable: self.is_retryable(),
+            },
+        }
+    }
+}
+
+pub async fn fetch_forecast_request<T>(transport: &dyn HttpTransport, request: HttpRequest) -> Result<Forecast, RequestError>
+    where T: WeatherProviderResponse + DeserializeOwned
+{
+    fetch_json_request::<T>(transport, request).await.map(|resp| resp.to_forecast())
+}
+
+pub async fn fetch_current_conditions_request<T>(transport: &dyn HttpTransport, request: HttpRequest) -> Result<CurrentConditions, RequestError>
+    where T: WeatherProviderCurrentResponse + DeserializeOwned
+{
+    fetch_json_request::<T>(transport, request).await.map(|resp| resp.to_current_conditions())
+}
+
+pub async fn fetch_json_request<T>(transport: &dyn HttpTransport, request: HttpRequest) -> Result<T, RequestError>
+    where T: DeserializeOwned
+{
+    let response = transport
+        .send(request.timeout(Duration::new(10, 0)))
+        .await
+        .map_err(|e| match e {
+            TransportError::Timeout {} => RequestError::ProviderTimeOut {},
+            TransportError::Connection { .. } => RequestError::UnknownError {},
+        })?;
+
+    // Unknown locations come back as 400 with an error body, so only auth and server failures are told by status.
+    match response.status {
+        401 | 403 => return Err(RequestError::Unauthorized { status: response.status }),
+        500..=599 => return Err(RequestError::ServerError { status: response.status }),
+        _ => {}
+    };
+
+    serde_json::from_slice::<T>(&response.body).map_err(|_| RequestError::InvalidResponse {})
+}
@@ -0,0 +1,220 @@
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use chrono_tz::Tz;
+use std::sync::Arc;
+use crate::transport::{HttpTransport, HttpRequest};
+use crate::oauth::{self, OAuthSigner};
+use chrono::*;
+
+use crate::{RawForecast};
+use crate::provider::{WeatherProvider, ProviderError, WeatherProviderResponse, WeatherProviderCurrentResponse};
+use crate::current::CurrentConditions;
+use crate::forecast::Forecast;
+use crate::location::{Location, LocationType};
+use crate::provider::capabilities::{ProviderCapabilities, RateLimit, Variable};
+
+use super::utils;
+
+mod test;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct YahooResponse {
+    forecasts: Vec<YahooDay>,
+    location: YahooLocation,
+}
+
+impl WeatherProviderResponse for YahooResponse {
+    fn to_forecast(&self) -> Forecast {
+        let tz: Tz = self.location.timezone_id.parse().unwrap();
+
+        Forecast::new(
+            self.forecasts
+                .iter()
+                .map(|fd| {
+                    let utc_datetime: DateTime<Utc> = DateTime::from_timestamp(fd.date, 0).unwrap_or_default();
+
+                    (
+                        utc_datetime.with_timezone(&tz).format("%Y-%m-%d").to_string(),
+                        fd.low + ((fd.high - fd.low) / 2.0),
+                    )
+                })
+                .collect::<RawForecast>(),
+            String::from(YahooProvider::NAME)
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct YahooLocation {
+    timezone_id: String
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct YahooDay {
+    date: i64,
+    low: f64,
+    high: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct YahooCurrentResponse {
+    current_observation: YahooObservation,
+}
+
+impl WeatherProviderCurrentResponse for YahooCurrentResponse {
+    fn to_current_conditions(&self) -> CurrentConditions {
+        let observation = &self.current_observation;
+
+        CurrentConditions {
+            provider: String::from(YahooProvider::NAME),
+            observed_at: observation.pub_date,
+            temperature: observation.condition.temperature,
+            humidity: observation.atmosphere.as_ref().and_then(|atmosphere| atmosphere.humidity),
+            wind_kph: observation.wind.as_ref().and_then(|wind| wind.speed),
+            condition: observation.condition.text.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct YahooObservation {
+    #[serde(rename = "pubDate")]
+    pub_date: i64,
+    condition: YahooCondition,
+    atmosphere: Option<YahooAtmosphere>,
+    wind: Option<YahooWind>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct YahooCondition {
+    temperature: f64,
+    text: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct YahooAtmosphere {
+    humidity: Option<f64>,
+}
+
+/// Speed is in km/h as the request asks for metric units (`u=c`).
+#[derive(Debug, Serialize, Deserialize)]
+struct YahooWind {
+    speed: Option<f64>,
+}
+
+#[async_trait]
+impl WeatherProvider for YahooProvider {
+    /// Airports are not known to Yahoo, see `build_query_params`.
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            name: Self::NAME.to_owned(),
+            horizon_days: Self::HORIZON_DAYS,
+            hourly: false,
+            variables: vec![Variable::Temperature, Variable::Humidity, Variable::Wind, Variable::Condition],
+            location_types: vec![LocationType::City, LocationType::Coordinates, LocationType::PostalCode, LocationType::Place],
+            rate_limit: Some(RateLimit { requests: 2_000, period_seconds: 24 * 3600 }),
+        }
+    }
+
+    async fn get_forecast(&self, location: &Location) -> Result<Forecast, ProviderError> {
+        let query_params = Self::build_query_params(location)?;
+
+        let forecast = utils::fetch_forecast_request::<YahooResponse>(self.transport.as_ref(), self.build_request(&query_params)?)
+            .await
+            .map_err(Self::request_error)?;
+
+        tracing::debug!(provider = Self::NAME, location = %location, days = forecast.len(), "forecast received");
+        Ok(forecast)
+    }
+
+    /// Forecast response carries the current observation as well.
+    async fn get_current_conditions(&self, location: &Location) -> Result<CurrentConditions, ProviderError> {
+        let query_params = Self::build_query_params(location)?;
+
+        let current = utils::fetch_current_conditions_request::<YahooCurrentResponse>(self.transport.as_ref(), self.build_request(&query_params)?)
+            .await
+            .map_err(Self::request_error)?;
+
+        tracing::debug!(provider = Self::NAME, location = %location, observed_at = current.observed_at, "current conditions received");
+        Ok(current)
+    }
+}
+
+pub struct YahooProvider {
+    transport: Arc<dyn HttpTransport>,
+    app_id: String,
+    signer: OAuthSigner,
+    base_uri: String,
+}
+
+impl YahooProvider {
+    pub const BASE_URI: &'static str = "https://weather-ydn-yql.media.yahoo.com/forecastrss";
+    const NAME: &'static str = "Yahoo";
+    const HORIZON_DAYS: u32 = 10;
+
+    pub fn new(transport: Arc<dyn HttpTransport>, app_id: String, secret: String, user_key: String) -> Self {
+        YahooProvider {
+            transport,
+            app_id,
+            signer: OAuthSigner::new(user_key, secret).with_version(),
+            base_uri: Self::BASE_URI.to_owned(),
+        }
+    }
+
+    /// Sends requests to another Yahoo compatible API (a fake upstream in tests), requests are signed for `base_uri`.
+    pub fn with_base_uri(mut self, base_uri: String) -> Self {
+        self.base_uri = base_uri;
+        self
+    }
+
+    fn build_request(&self, query_params: &BTreeMap<String, String>) -> Result<HttpRequest, ProviderError> {
+        let url = self.build_forecast_url(query_params);
+        let authorization = self.signer.authorization_header("GET", &url, &[])
+            .map_err(|e| ProviderError::RequestError {
+                reason: e.to_string(),
+                provider_name: Self::NAME.to_owned(),
+                retryable: false,
+            })?;
+
+        Ok(HttpRequest::get(url)
+            .header("X-Yahoo-App-Id", self.app_id.clone())
+            .header("Authorization", authorization))
+    }
+
+    fn request_error(e: utils::RequestError) -> ProviderError {
+        e.into_provider_error(Self::NAME)
+    }
+
+    /// Yahoo knows cities and postal codes by name, everything else only by coordinates.
+    fn build_query_params(location: &Location) -> Result<BTreeMap<String, String>, ProviderError> {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        match (location, location.coordinates()) {
+            (_, Some((lat, lon))) => {
+                params.insert("lat".to_owned(), lat.to_string());
+                params.insert("lon".to_owned(), lon.to_string());
+            }
+            (Location::City(name), _) => { params.insert("location".to_owned(), name.clone()); }
+            (Location::PostalCode(code), _) => { params.insert("location".to_owned(), code.clone()); }
+            _ => return Err(ProviderError::UnsupportedLocation {
+                location: location.to_string(),
+                provider_name: Self::NAME.to_owned(),
+            }),
+        };
+
+        params.insert("format".to_owned(), "json".to_owned());
+        params.insert("u".to_owned(), "c".to_owned());
+
+        Ok(params)
+    }
+
+    fn build_forecast_url(&self, query_params: &BTreeMap<String, String>) -> String {
+        let query = query_params
+            .iter()
+            .map(|(key, value)| format!("{}={}", oauth::percent_encode(key), oauth::percent_encode(value)))
+            .collect::<Vec<String>>()
+            .join("&");
+
+        format!("{}?{}", self.base_uri, query)
+    }
+}
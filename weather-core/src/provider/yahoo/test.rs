@@ -0,0 +1,172 @@
+#![cfg(test)]
+use super::*;
+use crate::transport::mock::{MockTransport, MockResponse};
+use crate::transport::cassette::CassetteTransport;
+use crate::oauth::OAuthSigner;
+use std::path::PathBuf;
+
+/// Dates are midnights in Moscow: 2019-03-26 and 2019-03-27.
+const FORECAST_RESPONSE: &str = r#"{
+    "location": {"city": "Moscow", "timezone_id": "Europe/Moscow"},
+    "current_observation": {
+        "condition": {"text": "Cloudy", "code": 26, "temperature": 4},
+        "pubDate": 1553598000
+    },
+    "forecasts": [
+        {"day": "Tue", "date": 1553547600, "low": 0, "high": 3, "text": "Cloudy", "code": 26},
+        {"day": "Wed", "date": 1553634000, "low": 1, "high": 5, "text": "Rain", "code": 12}
+    ]
+}"#;
+
+fn create_yahoo_provider(transport: MockTransport) -> (YahooProvider, Arc<MockTransport>) {
+    let transport = Arc::new(transport);
+
+    (YahooProvider::new(transport.clone(), String::from("app_id"), String::from("secret"), String::from("user_key")), transport)
+}
+
+#[actix_rt::test]
+async fn test_yahoo_return_forecast() {
+    let (provider, transport) = create_yahoo_provider(MockTransport::new().on(&["forecastrss"], MockResponse::json(FORECAST_RESPONSE)));
+
+    let forecast_opt = provider.get_forecast(&Location::City(String::from("Moscow"))).await;
+    assert!(forecast_opt.is_ok());
+
+    let forecast = forecast_opt.unwrap();
+    assert_eq!(forecast.get_temperature_at(String::from("2019-03-26")), Some(&1.5));
+    assert_eq!(forecast.get_temperature_at(String::from("2019-03-27")), Some(&3.0));
+
+    let request = &transport.requests()[0];
+    assert_eq!(request.url, "https://weather-ydn-yql.media.yahoo.com/forecastrss?format=json&location=Moscow&u=c");
+    assert_eq!(request.headers["X-Yahoo-App-Id"], "app_id");
+    assert!(request.headers["Authorization"].starts_with("OAuth oauth_consumer_key=\"user_key\", "));
+    assert!(request.headers["Authorization"].contains("oauth_version=\"1.0\""));
+}
+
+#[actix_rt::test]
+async fn test_yahoo_error_for_invalid_location() {
+    let (provider, _) = create_yahoo_provider(MockTransport::new().on(&["forecastrss"], MockResponse::json(r#"{"location": {}, "current_observation": {}, "forecasts": []}"#)));
+
+    assert!(provider.get_forecast(&Location::City(String::from("UnknownCityInUnknownCountry"))).await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_yahoo_skip_request_for_unsupported_location() {
+    let (provider, transport) = create_yahoo_provider(MockTransport::new());
+
+    match provider.get_forecast(&Location::Airport(String::from("SVO"))).await {
+        Err(ProviderError::UnsupportedLocation { .. }) => {}
+        _ => panic!("Unsupported location expected"),
+    }
+    assert!(transport.requests().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_yahoo_return_current_conditions() {
+    let (provider, _) = create_yahoo_provider(MockTransport::new().on(&["forecastrss"], MockResponse::json(FORECAST_RESPONSE)));
+
+    let current_opt = provider.get_current_conditions(&Location::City(String::from("Moscow"))).await;
+    assert!(current_opt.is_ok());
+    assert_eq!(current_opt.unwrap().observed_at, 1553598000);
+}
+
+fn cassette_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/cassettes/yahoo.json")
+}
+
+#[actix_rt::test]
+async fn test_yahoo_replay_recorded_responses() {
+    let transport = Arc::new(CassetteTransport::replay(&cassette_path()).unwrap());
+    let provider = YahooProvider::new(transport, String::from("app_id"), String::from("secret"), String::from("user_key"));
+    let location = Location::City(String::from("Moscow"));
+
+    assert!(provider.get_forecast(&location).await.is_ok());
+    assert!(provider.get_current_conditions(&location).await.is_ok());
+}
+
+/// Refreshes fixtures/cassettes/yahoo.json from the live API:
+/// `YAHOO_APP_ID=... YAHOO_SECRET=... YAHOO_USER_KEY=... cargo test -p weather_core record_yahoo_cassette -- --ignored`
+#[cfg(feature = "reqwest-transport")]
+#[actix_rt::test]
+#[ignore]
+async fn record_yahoo_cassette() {
+    let transport = CassetteTransport::record(&cassette_path(), Arc::new(crate::transport::ReqwestTransport::default()));
+    let provider = YahooProvider::new(
+        Arc::new(transport),
+        std::env::var("YAHOO_APP_ID").unwrap(),
+        std::env::var("YAHOO_SECRET").unwrap(),
+        std::env::var("YAHOO_USER_KEY").unwrap(),
+    );
+
+    provider.get_forecast(&Location::City(String::from("Moscow"))).await.unwrap();
+}
+
+#[test]
+fn test_yahoo_current_response_to_current_conditions() {
+    let response: YahooCurrentResponse = serde_json::from_str(r#"{
+        "location": {"city": "Moscow", "timezone_id": "Europe/Moscow"},
+        "current_observation": {
+            "wind": {"chill": 1, "direction": 270, "speed": 11.27},
+            "atmosphere": {"humidity": 72, "visibility": 16.1, "pressure": 1013.0, "rising": 0},
+            "condition": {"text": "Cloudy", "code": 26, "temperature": 4},
+            "pubDate": 1553598000
+        },
+        "forecasts": []
+    }"#).unwrap();
+
+    let current = response.to_current_conditions();
+    assert_eq!(current.observed_at, 1553598000);
+    assert_eq!(current.temperature, 4.0);
+    assert_eq!(current.humidity, Some(72.0));
+    assert_eq!(current.wind_kph, Some(11.27));
+    assert_eq!(current.condition, Some(String::from("Cloudy")));
+}
+
+#[test]
+fn test_yahoo_query_params_use_coordinates() {
+    let params = YahooProvider::build_query_params(&Location::Coordinates { lat: 55.75, lon: 37.61 }).unwrap();
+
+    assert_eq!(
+        create_yahoo_provider(MockTransport::new()).0.build_forecast_url(&params),
+        "https://weather-ydn-yql.media.yahoo.com/forecastrss?format=json&lat=55.75&lon=37.61&u=c"
+    );
+}
+
+#[test]
+fn test_yahoo_url_encode_location() {
+    let params = YahooProvider::build_query_params(&Location::City(String::from("New York"))).unwrap();
+
+    assert_eq!(
+        create_yahoo_provider(MockTransport::new()).0.build_forecast_url(&params),
+        "https://weather-ydn-yql.media.yahoo.com/forecastrss?format=json&location=New%20York&u=c"
+    );
+}
+
+#[actix_rt::test]
+async fn test_yahoo_sign_requests_for_base_uri() {
+    let (provider, transport) = create_yahoo_provider(MockTransport::new().on(&["forecastrss"], MockResponse::json(FORECAST_RESPONSE)));
+    let provider = provider.with_base_uri(String::from("http://127.0.0.1:8089/forecastrss"));
+
+    assert!(provider.get_forecast(&Location::City(String::from("Moscow"))).await.is_ok());
+
+    let request = &transport.requests()[0];
+    assert_eq!(request.url, "http://127.0.0.1:8089/forecastrss?format=json&location=Moscow&u=c");
+
+    let signer = OAuthSigner::new(String::from("user_key"), String::from("secret"));
+    assert!(signer.verify("GET", &request.url, &[], &request.headers["Authorization"]).is_ok());
+    assert!(signer.verify("GET", &request.url.replace("127.0.0.1:8089", "weather-ydn-yql.media.yahoo.com"), &[], &request.headers["Authorization"]).is_err());
+}
+
+#[test]
+fn test_yahoo_url_encode_cyrillic_location() {
+    let params = YahooProvider::build_query_params(&Location::City(String::from("Москва"))).unwrap();
+
+    assert_eq!(
+        create_yahoo_provider(MockTransport::new()).0.build_forecast_url(&params),
+        "https://weather-ydn-yql.media.yahoo.com/forecastrss?format=json&location=%D0%9C%D0%BE%D1%81%D0%BA%D0%B2%D0%B0&u=c"
+    );
+}
+
+#[test]
+fn test_yahoo_unsupported_airport_location() {
+    assert!(YahooProvider::build_query_params(&Location::Airport(String::from("SVO"))).is_err());
+}
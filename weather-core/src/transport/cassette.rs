@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use thiserror::Error;
+use url::Url;
+use crate::transport::{HttpTransport, HttpRequest, HttpResponse, TransportError};
+
+/// Query parameters carrying secrets (Apixu `key`), their values never reach the cassette.
+pub const SCRUBBED_QUERY_PARAMS: [&str; 1] = ["key"];
+
+/// Headers carrying secrets (Yahoo OAuth signature and application id).
+pub const SCRUBBED_HEADERS: [&str; 2] = ["Authorization", "X-Yahoo-App-Id"];
+
+const SCRUBBED: &str = "<scrubbed>";
+
+#[derive(Debug, Error)]
+pub enum CassetteError {
+    #[error("Can't access cassette {}: {}", .path, .reason)]
+    Io {
+        path: String,
+        reason: String,
+    },
+
+    #[error("Invalid cassette {}: {}", .path, .reason)]
+    Format {
+        path: String,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Url with sorted query and scrubbed secrets, see `normalize_url`.
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// Fixture file: request/response pairs in the order they were recorded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> Result<Self, CassetteError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| CassetteError::Io { path: path.display().to_string(), reason: e.to_string() })?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| CassetteError::Format { path: path.display().to_string(), reason: e.to_string() })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CassetteError> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| CassetteError::Format { path: path.display().to_string(), reason: e.to_string() })?;
+
+        fs::write(path, content + "\n")
+            .map_err(|e| CassetteError::Io { path: path.display().to_string(), reason: e.to_string() })
+    }
+}
+
+/// Sorts query parameters and replaces secret values, so a request matches its recording
+/// whatever the parameter order and the key it was recorded with.
+pub fn normalize_url(url: &str) -> String {
+    let mut parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return url.to_owned(),
+    };
+
+    if parsed.query().is_none() {
+        return parsed.into_string();
+    }
+
+    let mut pairs: Vec<(String, String)> = parsed.query_pairs()
+        .map(|(name, value)| match SCRUBBED_QUERY_PARAMS.contains(&name.as_ref()) {
+            true => (name.into_owned(), SCRUBBED.to_owned()),
+            false => (name.into_owned(), value.into_owned()),
+        })
+        .collect();
+    pairs.sort();
+
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    parsed.into_string()
+}
+
+fn record_request(request: &HttpRequest) -> RecordedRequest {
+    RecordedRequest {
+        method: String::from("GET"),
+        url: normalize_url(&request.url),
+        headers: request.headers.iter()
+            .map(|(name, value)| match SCRUBBED_HEADERS.iter().any(|header| header.eq_ignore_ascii_case(name)) {
+                true => (name.clone(), SCRUBBED.to_owned()),
+                false => (name.clone(), value.clone()),
+            })
+            .collect(),
+    }
+}
+
+enum Mode {
+    Record(Arc<dyn HttpTransport>),
+    Replay,
+}
+
+/// Record/replay transport for provider fixtures.
+///
+/// Recording proxies requests to the real transport and appends every answered request to the cassette file
+/// (transport errors are passed through and not recorded). Replaying serves recorded responses by method
+/// and normalized url, identical requests get the recorded answers in order, the last one repeats.
+/// Requests missing from the cassette fail with `TransportError::Connection`.
+pub struct CassetteTransport {
+    mode: Mode,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+    replayed: Mutex<Vec<usize>>,
+}
+
+impl CassetteTransport {
+    /// Starts an empty cassette at `path`, the file is rewritten after every recorded interaction.
+    pub fn record(path: &Path, transport: Arc<dyn HttpTransport>) -> Self {
+        CassetteTransport {
+            mode: Mode::Record(transport),
+            path: path.to_owned(),
+            cassette: Mutex::new(Cassette::default()),
+            replayed: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn replay(path: &Path) -> Result<Self, CassetteError> {
+        let cassette = Cassette::load(path)?;
+        let replayed = vec![0; cassette.interactions.len()];
+
+        Ok(CassetteTransport {
+            mode: Mode::Replay,
+            path: path.to_owned(),
+            cassette: Mutex::new(cassette),
+            replayed: Mutex::new(replayed),
+        })
+    }
+
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().unwrap().clone()
+    }
+
+    fn find_recorded(&self, request: &RecordedRequest) -> Option<HttpResponse> {
+        let cassette = self.cassette.lock().unwrap();
+        let mut replayed = self.replayed.lock().unwrap();
+
+        let matching: Vec<usize> = cassette.interactions.iter()
+            .enumerate()
+            .filter(|(_, interaction)| interaction.request.method == request.method && interaction.request.url == request.url)
+            .map(|(index, _)| index)
+            .collect();
+
+        let index = matching.iter()
+            .find(|index| replayed[**index] == 0)
+            .or_else(|| matching.last())
+            .copied()?;
+        replayed[index] += 1;
+
+        let response = &cassette.interactions[index].response;
+        Some(HttpResponse { status: response.status, body: response.body.as_bytes().to_vec() })
+    }
+}
+
+#[async_trait]
+impl HttpTransport for CassetteTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+        let recorded = record_request(&request);
+
+        match self.mode {
+            Mode::Replay => self.find_recorded(&recorded)
+                .ok_or_else(|| TransportError::Connection { reason: format!("No recorded interaction for {}", recorded.url) }),
+            Mode::Record(ref transport) => {
+                let response = transport.send(request).await?;
+
+                let mut cassette = self.cassette.lock().unwrap();
+                cassette.interactions.push(Interaction {
+                    request: recorded,
+                    response: RecordedResponse { status: response.status, body: String::from_utf8_lossy(&response.body).into_owned() },
+                });
+                cassette.save(&self.path)
+                    .map_err(|e| TransportError::Connection { reason: e.to_string() })?;
+
+                Ok(response)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod cassette_transport_test {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use futures::executor::block_on;
+    use crate::transport::{HttpTransport, HttpRequest, TransportError};
+    use crate::transport::mock::{MockTransport, MockResponse};
+    use crate::transport::cassette::*;
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("weather-core-{}-{}.json", name, std::process::id()))
+    }
+
+    fn yahoo_request(location: &str) -> HttpRequest {
+        HttpRequest::get(format!("https://weather-ydn-yql.media.yahoo.com/forecastrss?location={}&format=json&u=c", location))
+            .header("Authorization", String::from("OAuth oauth_consumer_key=user_key, oauth_signature=secret"))
+            .header("X-Yahoo-App-Id", String::from("app_id"))
+    }
+
+    #[test]
+    fn test_normalize_url_sort_query_and_scrub_key() {
+        assert_eq!(
+            normalize_url("http://api.apixu.com/v1/forecast.json?q=New+York&key=secret&days=7"),
+            "http://api.apixu.com/v1/forecast.json?days=7&key=%3Cscrubbed%3E&q=New+York"
+        );
+        assert_eq!(normalize_url("http://host/forecast"), "http://host/forecast");
+    }
+
+    #[test]
+    fn test_record_scrub_secrets() {
+        let path = cassette_path("record");
+        let inner = Arc::new(MockTransport::new().on(&["yahoo", "forecastrss"], MockResponse::json("{}")).on(&["forecast.json"], MockResponse::json("{}")));
+        let transport = CassetteTransport::record(&path, inner);
+
+        assert!(block_on(transport.send(yahoo_request("Moscow"))).is_ok());
+        assert!(block_on(transport.send(HttpRequest::get(String::from("http://api.apixu.com/v1/forecast.json?key=secret&q=Moscow")))).is_ok());
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!saved.contains("secret"));
+        assert!(!saved.contains("app_id"));
+        assert_eq!(serde_json::from_str::<Cassette>(&saved).unwrap().interactions.len(), 2);
+    }
+
+    #[test]
+    fn test_record_does_not_keep_transport_errors() {
+        let path = cassette_path("record-error");
+        let transport = CassetteTransport::record(&path, Arc::new(MockTransport::new()));
+
+        assert!(block_on(transport.send(yahoo_request("Moscow"))).is_err());
+        assert!(transport.cassette().interactions.is_empty());
+    }
+
+    #[test]
+    fn test_replay_recorded_responses() {
+        let path = cassette_path("replay");
+        let inner = Arc::new(MockTransport::new()
+            .on_sequence(&["forecastrss"], vec![MockResponse::json("first"), MockResponse::status(500, "second")]));
+        let recorder = CassetteTransport::record(&path, inner);
+        block_on(recorder.send(yahoo_request("Moscow"))).unwrap();
+        block_on(recorder.send(yahoo_request("Moscow"))).unwrap();
+
+        let transport = CassetteTransport::replay(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let request = HttpRequest::get(String::from("https://weather-ydn-yql.media.yahoo.com/forecastrss?u=c&format=json&location=Moscow"));
+        assert_eq!(block_on(transport.send(request.clone())).unwrap().body, b"first".to_vec());
+        assert_eq!(block_on(transport.send(request.clone())).unwrap().status, 500);
+        assert_eq!(block_on(transport.send(request)).unwrap().status, 500);
+
+        match block_on(transport.send(yahoo_request("Paris"))) {
+            Err(TransportError::Connection { .. }) => {}
+            _ => panic!("Connection error expected"),
+        }
+    }
+
+    #[test]
+    fn test_replay_fail_on_missing_cassette() {
+        match CassetteTransport::replay(&cassette_path("missing")) {
+            Err(CassetteError::Io { .. }) => {}
+            _ => panic!("Io error expected"),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use async_trait::async_trait;
+use crate::transport::{HttpTransport, HttpRequest, HttpResponse, TransportError};
+
+/// Scripted outcome of a mocked request.
+#[derive(Debug)]
+pub struct MockResponse {
+    result: Result<HttpResponse, TransportError>,
+    delay: Option<Duration>,
+}
+
+impl MockResponse {
+    pub fn json(body: &str) -> Self {
+        MockResponse::status(200, body)
+    }
+
+    pub fn status(status: u16, body: &str) -> Self {
+        MockResponse {
+            result: Ok(HttpResponse { status, body: body.as_bytes().to_vec() }),
+            delay: None,
+        }
+    }
+
+    pub fn error(error: TransportError) -> Self {
+        MockResponse {
+            result: Err(error),
+            delay: None,
+        }
+    }
+
+    /// Answers after `delay`, delays not shorter than the request timeout end up in `TransportError::Timeout`
+    /// right away, without waiting for it.
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    fn replay(&self) -> Result<HttpResponse, TransportError> {
+        match self.result {
+            Ok(ref response) => Ok(response.clone()),
+            Err(TransportError::Timeout {}) => Err(TransportError::Timeout {}),
+            Err(TransportError::Connection { ref reason }) => Err(TransportError::Connection { reason: reason.clone() }),
+        }
+    }
+}
+
+struct MockRoute {
+    url_fragments: Vec<String>,
+    responses: Vec<MockResponse>,
+    calls: usize,
+}
+
+impl MockRoute {
+    fn matches(&self, url: &str) -> bool {
+        self.url_fragments.iter().all(|fragment| url.contains(fragment.as_str()))
+    }
+
+    /// Responses go in order, the last one repeats.
+    fn next_response(&mut self) -> &MockResponse {
+        let index = self.calls.min(self.responses.len() - 1);
+        self.calls += 1;
+
+        &self.responses[index]
+    }
+}
+
+/// In-memory transport answering with scripted responses, for tests that must not touch the network.
+///
+/// Requests go to the first route whose url fragments are all found in the request url,
+/// requests matching no route fail with `TransportError::Connection`.
+#[derive(Default)]
+pub struct MockTransport {
+    routes: Mutex<Vec<MockRoute>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        MockTransport::default()
+    }
+
+    pub fn on(self, url_fragments: &[&str], response: MockResponse) -> Self {
+        self.on_sequence(url_fragments, vec![response])
+    }
+
+    pub fn on_sequence(self, url_fragments: &[&str], responses: Vec<MockResponse>) -> Self {
+        assert!(!responses.is_empty(), "Mock route without responses");
+
+        self.routes.lock().unwrap().push(MockRoute {
+            url_fragments: url_fragments.iter().map(|fragment| (*fragment).to_owned()).collect(),
+            responses,
+            calls: 0,
+        });
+
+        self
+    }
+
+    /// Requests sent so far, in order.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl HttpTransport for MockTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+        self.requests.lock().unwrap().push(request.clone());
+
+        let (result, delay) = {
+            let mut routes = self.routes.lock().unwrap();
+
+            match routes.iter_mut().find(|route| route.matches(&request.url)) {
+                Some(route) => {
+                    let response = route.next_response();
+                    (response.replay(), response.delay)
+                }
+                None => (Err(TransportError::Connection { reason: format!("No mocked response for {}", request.url) }), None),
+            }
+        };
+
+        match (delay, request.timeout) {
+            (Some(delay), Some(timeout)) if delay >= timeout => return Err(TransportError::Timeout {}),
+            (Some(delay), _) => tokio::time::sleep(delay).await,
+            _ => {}
+        };
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod mock_transport_test {
+    use std::time::Duration;
+    use futures::executor::block_on;
+    use crate::transport::{HttpTransport, HttpRequest, TransportError};
+    use crate::transport::mock::{MockTransport, MockResponse};
+
+    #[test]
+    fn test_first_matching_route_answers() {
+        let transport = MockTransport::new()
+            .on(&["forecast", "q=ascx"], MockResponse::status(400, "{}"))
+            .on(&["forecast"], MockResponse::json("[]"));
+
+        assert_eq!(block_on(transport.send(HttpRequest::get(String::from("http://host/forecast?q=ascx")))).unwrap().status, 400);
+        assert_eq!(block_on(transport.send(HttpRequest::get(String::from("http://host/forecast?q=Moscow")))).unwrap().body, b"[]".to_vec());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_sequence_repeats_last_response() {
+        let transport = MockTransport::new()
+            .on_sequence(&["forecast"], vec![MockResponse::error(TransportError::Connection { reason: String::from("reset") }), MockResponse::json("[]")]);
+
+        let request = HttpRequest::get(String::from("http://host/forecast"));
+
+        assert!(block_on(transport.send(request.clone())).is_err());
+        assert!(block_on(transport.send(request.clone())).is_ok());
+        assert!(block_on(transport.send(request)).is_ok());
+    }
+
+    #[test]
+    fn test_unmatched_request_fail() {
+        let result = block_on(MockTransport::new().send(HttpRequest::get(String::from("http://host/forecast"))));
+
+        match result {
+            Err(TransportError::Connection { .. }) => {}
+            _ => panic!("Connection error expected"),
+        }
+    }
+
+    #[test]
+    fn test_delay_beyond_timeout_is_timeout() {
+        let transport = MockTransport::new().on(&["forecast"], MockResponse::json("[]").delayed(Duration::from_secs(30)));
+        let request = HttpRequest::get(String::from("http://host/forecast")).timeout(Duration::from_secs(10));
+
+        match block_on(transport.send(request)) {
+            Err(TransportError::Timeout {}) => {}
+            _ => panic!("Timeout expected"),
+        }
+    }
+}
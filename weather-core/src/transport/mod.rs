@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+use async_trait::async_trait;
+use thiserror::Error;
+
+#[cfg(feature = "reqwest-transport")]
+mod reqwest_transport;
+
+#[cfg(feature = "reqwest-transport")]
+pub use self::reqwest_transport::ReqwestTransport;
+
+#[cfg(any(test, feature = "mock-transport"))]
+pub mod mock;
+
+pub mod cassette;
+
+#[derive(Debug, Error)]
+pub enum TransportError {
+    #[error("Request timed out")]
+    Timeout {},
+
+    #[error("Connection error: {}", .reason)]
+    Connection {
+        reason: String,
+    },
+}
+
+/// GET request as providers send it, the transport knows nothing about providers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub timeout: Option<Duration>,
+}
+
+impl HttpRequest {
+    pub fn get(url: String) -> Self {
+        HttpRequest {
+            url,
+            headers: BTreeMap::new(),
+            timeout: None,
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: String) -> Self {
+        self.headers.insert(name.to_owned(), value);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Sends provider requests, lets the aggregator run on any HTTP client (or none at all).
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, TransportError>;
+}
@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use crate::transport::{HttpTransport, HttpRequest, HttpResponse, TransportError};
+
+/// Transport over a shared `reqwest` client, keeps the connection pool between requests.
+#[derive(Clone, Default)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: Client) -> Self {
+        ReqwestTransport { client }
+    }
+
+    fn error(e: reqwest::Error) -> TransportError {
+        match e.is_timeout() {
+            true => TransportError::Timeout {},
+            _ => TransportError::Connection { reason: e.to_string() },
+        }
+    }
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+        let mut builder = self.client.get(request.url.as_str());
+
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        if let Some(timeout) = request.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        let response = builder.send().await.map_err(Self::error)?;
+        let status = response.status().as_u16();
+        let body = response.bytes().await.map_err(Self::error)?;
+
+        Ok(HttpResponse {
+            status,
+            body: body.to_vec(),
+        })
+    }
+}
@@ -0,0 +1,193 @@
+use crate::Date;
+use crate::forecast::ForecastError;
+use crate::provider::ProviderError;
+use crate::current::CurrentConditionsError;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCode {
+    RequestError,
+    UnsupportedDate,
+    UnsupportedLocation,
+    UnsupportedRange,
+    ToShortForecast,
+    PartialCoverage,
+    StaleObservation,
+    BeyondHorizon,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Warning {
+    pub code: WarningCode,
+    pub provider: Option<String>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
+    pub date: Option<Date>,
+    pub message: String,
+    pub retryable: bool,
+    /// Id of the HTTP request the warning was raised in, set by the web layer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl Warning {
+    /// Renders warning as a free-form string, the way v1 clients expect it.
+    pub fn to_legacy_string(&self) -> String {
+        self.message.clone()
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+}
+
+impl From<ProviderError> for Warning {
+    fn from(e: ProviderError) -> Self {
+        let message = e.to_string();
+
+        match e {
+            ProviderError::RequestError { provider_name, retryable, .. } => Warning {
+                code: WarningCode::RequestError,
+                provider: Some(provider_name),
+                date: None,
+                message,
+                retryable,
+                request_id: None,
+            },
+            ProviderError::Unauthorized { provider_name, .. } => Warning {
+                code: WarningCode::RequestError,
+                provider: Some(provider_name),
+                date: None,
+                message,
+                retryable: false,
+                request_id: None,
+            },
+            ProviderError::UnsupportedDate { date, provider_name } => Warning {
+                code: WarningCode::UnsupportedDate,
+                provider: Some(provider_name),
+                date: Some(date),
+                message,
+                retryable: false,
+                request_id: None,
+            },
+            ProviderError::UnsupportedLocation { provider_name, .. } => Warning {
+                code: WarningCode::UnsupportedLocation,
+                provider: Some(provider_name),
+                date: None,
+                message,
+                retryable: false,
+                request_id: None,
+            },
+        }
+    }
+}
+
+impl From<ForecastError> for Warning {
+    fn from(e: ForecastError) -> Self {
+        let message = e.to_string();
+
+        match e {
+            ForecastError::UnsupportedDate { date, provider_name } => Warning {
+                code: WarningCode::UnsupportedDate,
+                provider: Some(provider_name),
+                date: Some(date),
+                message,
+                retryable: false,
+                request_id: None,
+            },
+            ForecastError::ToShortForecast { provider_name } => Warning {
+                code: WarningCode::ToShortForecast,
+                provider: Some(provider_name),
+                date: None,
+                message,
+                retryable: false,
+                request_id: None,
+            },
+            ForecastError::UnsupportedRange { from, provider_name, .. } => Warning {
+                code: WarningCode::UnsupportedRange,
+                provider: Some(provider_name),
+                date: Some(from),
+                message,
+                retryable: false,
+                request_id: None,
+            },
+            ForecastError::BeyondHorizon { from, provider_name, .. } => Warning {
+                code: WarningCode::BeyondHorizon,
+                provider: Some(provider_name),
+                date: Some(from),
+                message,
+                retryable: false,
+                request_id: None,
+            },
+            ForecastError::PastPeriod { to, provider_name, .. } => Warning {
+                code: WarningCode::BeyondHorizon,
+                provider: Some(provider_name),
+                date: Some(to),
+                message,
+                retryable: false,
+                request_id: None,
+            },
+            ForecastError::PartialCoverage { date, provider_name, .. } => Warning {
+                code: WarningCode::PartialCoverage,
+                provider: Some(provider_name),
+                date: Some(date),
+                message,
+                retryable: false,
+                request_id: None,
+            },
+        }
+    }
+}
+
+impl From<CurrentConditionsError> for Warning {
+    fn from(e: CurrentConditionsError) -> Self {
+        let message = e.to_string();
+
+        match e {
+            CurrentConditionsError::StaleObservation { provider_name, .. } => Warning {
+                code: WarningCode::StaleObservation,
+                provider: Some(provider_name),
+                date: None,
+                message,
+                retryable: true,
+                request_id: None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod warning_test {
+    use crate::warning::{Warning, WarningCode};
+    use crate::provider::ProviderError;
+    use crate::forecast::ForecastError;
+
+    #[test]
+    fn test_warning_from_unsupported_date_keep_legacy_message() {
+        let warning = Warning::from(ForecastError::UnsupportedDate {
+            date: String::from("2019-03-31"),
+            provider_name: String::from("Apixu"),
+        });
+
+        assert_eq!(warning.code, WarningCode::UnsupportedDate);
+        assert_eq!(warning.provider, Some(String::from("Apixu")));
+        assert_eq!(warning.date, Some(String::from("2019-03-31")));
+        assert_eq!(warning.to_legacy_string(), "Unsupported date 2019-03-31 for provider Apixu !");
+        assert!(!warning.retryable);
+    }
+
+    #[test]
+    fn test_warning_from_request_error_keep_retryable_flag() {
+        let warning = Warning::from(ProviderError::RequestError {
+            reason: String::from("Request time out"),
+            provider_name: String::from("Yahoo"),
+            retryable: true,
+        });
+
+        assert_eq!(warning.code, WarningCode::RequestError);
+        assert_eq!(warning.date, None);
+        assert!(warning.retryable);
+    }
+}